@@ -1,20 +1,122 @@
+use flate2::read::MultiGzDecoder;
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::{
   collections::{HashMap, HashSet},
+  io::Read,
   sync::LazyLock,
 };
 use texting_robots::Robot;
 use url::Url;
 
+use crate::robots::{RobotsTxt, RobotsTxtRule};
+use crate::seen::SeenSet;
+
 static FILE_EXTENSIONS: &[&str] = &[
   ".png", ".jpg", ".jpeg", ".gif", ".css", ".js", ".ico", ".svg", ".tiff", ".zip", ".exe", ".dmg",
   ".mp4", ".mp3", ".wav", ".pptx", ".xlsx", ".avi", ".flv", ".woff", ".ttf", ".woff2", ".webp",
   ".inc",
 ];
 
+/// Known file extensions with their MIME type and category, used to resolve
+/// `include_file_types`. Only `FILE_EXTENSIONS` are rejected by default; the
+/// rest can be rejected per crawl with `exclude_extensions`.
+static FILE_TYPES: &[(&str, &str, &str)] = &[
+  (".png", "image/png", "images"),
+  (".jpg", "image/jpeg", "images"),
+  (".jpeg", "image/jpeg", "images"),
+  (".gif", "image/gif", "images"),
+  (".svg", "image/svg+xml", "images"),
+  (".tiff", "image/tiff", "images"),
+  (".webp", "image/webp", "images"),
+  (".ico", "image/vnd.microsoft.icon", "images"),
+  (".bmp", "image/bmp", "images"),
+  (".avif", "image/avif", "images"),
+  (".pdf", "application/pdf", "documents"),
+  (".doc", "application/msword", "documents"),
+  (
+    ".docx",
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    "documents",
+  ),
+  (".xls", "application/vnd.ms-excel", "documents"),
+  (
+    ".xlsx",
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    "documents",
+  ),
+  (".ppt", "application/vnd.ms-powerpoint", "documents"),
+  (
+    ".pptx",
+    "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+    "documents",
+  ),
+  (
+    ".odt",
+    "application/vnd.oasis.opendocument.text",
+    "documents",
+  ),
+  (
+    ".ods",
+    "application/vnd.oasis.opendocument.spreadsheet",
+    "documents",
+  ),
+  (
+    ".odp",
+    "application/vnd.oasis.opendocument.presentation",
+    "documents",
+  ),
+  (".rtf", "application/rtf", "documents"),
+  (".csv", "text/csv", "documents"),
+  (".epub", "application/epub+zip", "documents"),
+  (".mp4", "video/mp4", "media"),
+  (".avi", "video/x-msvideo", "media"),
+  (".flv", "video/x-flv", "media"),
+  (".mov", "video/quicktime", "media"),
+  (".webm", "video/webm", "media"),
+  (".mkv", "video/x-matroska", "media"),
+  (".mp3", "audio/mpeg", "media"),
+  (".wav", "audio/wav", "media"),
+  (".ogg", "audio/ogg", "media"),
+  (".m4a", "audio/mp4", "media"),
+  (".flac", "audio/flac", "media"),
+  (".zip", "application/zip", "archives"),
+  (".tar", "application/x-tar", "archives"),
+  (".gz", "application/gzip", "archives"),
+  (".tgz", "application/gzip", "archives"),
+  (".bz2", "application/x-bzip2", "archives"),
+  (".rar", "application/vnd.rar", "archives"),
+  (".7z", "application/x-7z-compressed", "archives"),
+  (
+    ".exe",
+    "application/vnd.microsoft.portable-executable",
+    "executables",
+  ),
+  (".dmg", "application/x-apple-diskimage", "executables"),
+  (".css", "text/css", "assets"),
+  (".js", "text/javascript", "assets"),
+  (".mjs", "text/javascript", "assets"),
+  (".map", "application/json", "assets"),
+  (".inc", "text/plain", "assets"),
+  (".woff", "font/woff", "assets"),
+  (".woff2", "font/woff2", "assets"),
+  (".ttf", "font/ttf", "assets"),
+  (".otf", "font/otf", "assets"),
+  (".eot", "application/vnd.ms-fontobject", "assets"),
+];
+
+/// Categories that `include_file_types` may opt back in to. Stylesheets,
+/// scripts, fonts and executables are never crawlable through a category.
+const FILE_TYPE_CATEGORIES: &[&str] = &["images", "documents", "media", "archives"];
+
+/// Sitemaps are capped at 50MB uncompressed by the protocol; anything larger
+/// coming out of a gzip stream is treated as a zip bomb.
+const MAX_SITEMAP_DECOMPRESSED_SIZE: u32 = 50 * 1024 * 1024;
+
+const DEFAULT_ROBOTS_USER_AGENTS: [&str; 2] = ["FireCrawlAgent", "FirecrawlAgent"];
+
 static FILE_EXT_SET: LazyLock<HashSet<&'static str>> =
   LazyLock::new(|| FILE_EXTENSIONS.iter().copied().collect());
 
@@ -22,18 +124,75 @@ static FILE_EXT_SET: LazyLock<HashSet<&'static str>> =
 #[napi(object)]
 pub struct FilterLinksCall {
   pub links: Vec<String>,
-  pub limit: Option<i64>,
-  pub max_depth: u32,
+  pub options: LinkFilterOptions,
+}
+
+/// Options shared by `filter_url` and `LinkFilter` for deciding on a single
+/// URL.
+#[derive(Deserialize)]
+#[napi(object)]
+pub struct UrlRuleOptions {
   pub base_url: String,
-  pub initial_url: String,
-  pub regex_on_full_url: bool,
   pub excludes: Vec<String>,
-  pub includes: Vec<String>,
-  pub allow_backward_crawling: bool,
+  /// Gitignore-style path globs (`/blog/**`, `!/blog/drafts/*`) an internal link
+  /// must match, in addition to `includes` when filtering links.
+  pub include_globs: Option<Vec<String>>,
+  /// Gitignore-style path globs that exclude internal links, in addition to `excludes`.
+  /// A `!` pattern only makes exceptions to earlier ones; a leading one is invalid.
+  pub exclude_globs: Option<Vec<String>>,
+  /// How link depth and URL identity are computed beyond path segments.
+  pub depth_strategy: Option<DepthStrategy>,
   pub ignore_robots_txt: bool,
   pub robots_txt: String,
   pub allow_external_content_links: bool,
   pub allow_subdomains: bool,
+  /// Robots.txt user-agent tokens in priority order. Defaults to
+  /// `["FireCrawlAgent", "FirecrawlAgent"]`.
+  pub user_agents: Option<Vec<String>>,
+  /// Registrable domains denied as `SOCIAL_MEDIA`; replaces the built-in list when set.
+  pub blocked_domains: Option<Vec<String>>,
+  /// Registrable domains exempt from the social media/blocked domain check.
+  pub allowed_domains: Option<Vec<String>>,
+}
+
+/// Depth and URL identity rules for sites that route through fragments or
+/// query strings.
+#[derive(Deserialize, Clone, Default)]
+#[napi(object)]
+pub struct DepthStrategy {
+  /// `true` treats `#/route/segments` as path levels, `false` treats every
+  /// fragment as a section anchor. Unset keeps route fragments without
+  /// counting them.
+  pub hash_routes: Option<bool>,
+  /// Query params that each add one level when present, e.g. `["page"]`.
+  pub query_params_as_levels: Option<Vec<String>>,
+  /// Query params that do not make a URL distinct and are dropped when
+  /// normalizing, e.g. session ids or sort orders.
+  pub ignore_query_params: Option<Vec<String>>,
+}
+
+/// Crawl rules for `filter_links` and a reusable `LinkFilter`.
+#[derive(Deserialize)]
+#[napi(object)]
+pub struct LinkFilterOptions {
+  pub rules: UrlRuleOptions,
+  pub limit: Option<i64>,
+  pub max_depth: u32,
+  pub initial_url: String,
+  pub regex_on_full_url: bool,
+  pub includes: Vec<String>,
+  pub allow_backward_crawling: bool,
+  /// Extensions to crawl even though they are rejected by default (e.g. `".pdf"`).
+  pub include_extensions: Option<Vec<String>>,
+  /// Extra extensions to reject. Takes precedence over every include option.
+  pub exclude_extensions: Option<Vec<String>>,
+  /// File types to crawl: categories (`"images"`, `"documents"`, `"media"`,
+  /// `"archives"`) or MIME types such as `"application/pdf"` or `"image/*"`.
+  pub include_file_types: Option<Vec<String>>,
+  /// Fill `FilterLinksResult.denial_details` with the rule behind each denial.
+  pub explain_denials: Option<bool>,
+  /// Return canonical URLs (see `normalize_url`) and deny repeats as `DUPLICATE`.
+  pub deduplicate: Option<bool>,
 }
 
 #[derive(Serialize)]
@@ -41,6 +200,11 @@ pub struct FilterLinksCall {
 pub struct FilterLinksResult {
   pub links: Vec<String>,
   pub denial_reasons: HashMap<String, String>,
+  /// Structured reasons keyed like `denial_reasons`. Only set with `explain_denials`.
+  pub denial_details: Option<HashMap<String, LinkDenial>>,
+  /// Include/exclude patterns that failed to compile, with the error. They are
+  /// ignored while filtering.
+  pub invalid_patterns: HashMap<String, String>,
 }
 
 #[derive(Deserialize)]
@@ -48,12 +212,32 @@ pub struct FilterLinksResult {
 pub struct FilterUrlCall {
   pub href: String,
   pub url: String,
-  pub base_url: String,
-  pub excludes: Vec<String>,
-  pub ignore_robots_txt: bool,
-  pub robots_txt: String,
-  pub allow_external_content_links: bool,
-  pub allow_subdomains: bool,
+  pub rules: UrlRuleOptions,
+}
+
+/// Why a link was denied, with the rule that decided it.
+#[derive(Serialize, Debug, Clone, Default)]
+#[napi(object)]
+pub struct LinkDenial {
+  /// Denial code, same as in `denial_reasons`.
+  pub reason: String,
+  /// Absolute URL the link resolved to against `base_url`.
+  pub resolved_url: Option<String>,
+  /// Regex or glob that excluded the link, or the negated include glob that
+  /// rejected it.
+  pub pattern: Option<String>,
+  pub depth: Option<u32>,
+  pub max_depth: Option<u32>,
+  /// robots.txt rule that disallowed the link.
+  pub robots_txt_rule: Option<RobotsTxtRule>,
+  /// User agent the robots.txt rules were evaluated for.
+  pub robots_user_agent: Option<String>,
+  /// Rejected file extension.
+  pub extension: Option<String>,
+  /// Registrable domain of a social media or external link.
+  pub domain: Option<String>,
+  /// Canonical URL of the earlier link this one duplicates.
+  pub duplicate_of: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -62,18 +246,104 @@ pub struct FilterUrlResult {
   pub allowed: bool,
   pub url: Option<String>,
   pub denial_reason: Option<String>,
+  /// Exclude patterns and globs that failed to compile, with the error. They
+  /// are ignored while filtering.
+  pub invalid_patterns: HashMap<String, String>,
 }
 
 #[derive(Serialize, Debug)]
 #[napi(object)]
 pub struct SitemapUrl {
   pub loc: Vec<String>,
+  /// `<lastmod>` as written, in W3C datetime format.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub lastmod: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub changefreq: Option<String>,
+  /// `<priority>`, omitted when missing or not a number.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub priority: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub images: Option<Vec<SitemapImage>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub videos: Option<Vec<SitemapVideo>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub news: Option<SitemapNews>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub alternates: Option<Vec<SitemapAlternate>>,
+}
+
+/// `<image:image>` entry from Google's image sitemap extension.
+#[derive(Serialize, Debug, PartialEq)]
+#[napi(object)]
+pub struct SitemapImage {
+  pub loc: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub caption: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub title: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub geo_location: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub license: Option<String>,
+}
+
+/// `<video:video>` entry from Google's video sitemap extension.
+#[derive(Serialize, Debug, PartialEq)]
+#[napi(object)]
+pub struct SitemapVideo {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub thumbnail_loc: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub title: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub description: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub content_loc: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub player_loc: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub duration: Option<u32>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub publication_date: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub expiration_date: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub family_friendly: Option<bool>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub tags: Option<Vec<String>>,
+}
+
+/// `<news:news>` entry from Google's news sitemap extension.
+#[derive(Serialize, Debug, PartialEq)]
+#[napi(object)]
+pub struct SitemapNews {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub publication_name: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub publication_language: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub publication_date: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub title: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub keywords: Option<String>,
+}
+
+/// `<xhtml:link rel="alternate" hreflang="..">` language variant of a URL.
+#[derive(Serialize, Debug, PartialEq)]
+#[napi(object)]
+pub struct SitemapAlternate {
+  pub hreflang: String,
+  pub href: String,
 }
 
 #[derive(Serialize, Debug)]
 #[napi(object)]
 pub struct SitemapEntry {
   pub loc: Vec<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub lastmod: Option<String>,
 }
 
 #[derive(Serialize, Debug)]
@@ -95,6 +365,27 @@ pub struct ParsedSitemap {
   pub sitemapindex: Option<SitemapIndex>,
 }
 
+#[derive(Deserialize, Default)]
+#[napi(object)]
+pub struct ProcessSitemapOptions {
+  /// Only keep entries whose `<lastmod>` is after this W3C datetime. Entries
+  /// without a (parseable) `<lastmod>` are kept.
+  pub modified_after: Option<String>,
+  /// Only keep URLs whose `<priority>` is at least this value (default 0.5).
+  pub min_priority: Option<f64>,
+  /// Only keep URLs whose `<changefreq>` is one of these values.
+  pub changefreqs: Option<Vec<String>>,
+  /// Sort processed URLs by "lastmod" (newest first) or "priority" (highest first).
+  pub sort_by: Option<String>,
+  /// Also emit `xhtml:link` hreflang alternates as URLs to process.
+  pub include_alternates: Option<bool>,
+  /// Upper bound in bytes for gzip-compressed input once inflated.
+  pub max_decompressed_size: Option<u32>,
+  /// URL the sitemap was fetched from. Relative feed links are resolved
+  /// against it.
+  pub base_url: Option<String>,
+}
+
 #[derive(Serialize, Debug)]
 #[napi(object)]
 pub struct SitemapInstruction {
@@ -111,7 +402,7 @@ pub struct SitemapProcessingResult {
 }
 
 const URL_PARSE_ERROR: &str = "URL_PARSE_ERROR";
-const DEPTH_LIMIT: &str = "DEPTH_LIMIT";
+pub(crate) const DEPTH_LIMIT: &str = "DEPTH_LIMIT";
 const EXCLUDE_PATTERN: &str = "EXCLUDE_PATTERN";
 const INCLUDE_PATTERN: &str = "INCLUDE_PATTERN";
 const BACKWARD_CRAWLING: &str = "BACKWARD_CRAWLING";
@@ -120,6 +411,13 @@ const FILE_TYPE: &str = "FILE_TYPE";
 const SOCIAL_MEDIA: &str = "SOCIAL_MEDIA";
 const EXTERNAL_LINK: &str = "EXTERNAL_LINK";
 const SECTION_LINK: &str = "SECTION_LINK";
+pub(crate) const DUPLICATE: &str = "DUPLICATE";
+
+/// Query parameters that only carry click/campaign tracking.
+const TRACKING_QUERY_PARAMS: &[&str] = &[
+  "fbclid", "gclid", "dclid", "gbraid", "wbraid", "msclkid", "yclid", "igshid", "mc_cid", "mc_eid",
+  "_ga", "_gl", "_hsenc", "_hsmi",
+];
 
 #[inline]
 fn is_file(path: &str) -> bool {
@@ -131,6 +429,93 @@ fn is_file(path: &str) -> bool {
   }
 }
 
+#[inline]
+fn path_extension(path: &str) -> Option<&str> {
+  path.rfind('.').map(|dot_pos| &path[dot_pos..])
+}
+
+#[inline]
+fn normalize_extension(extension: &str) -> String {
+  let extension = extension.trim().to_lowercase();
+  if extension.starts_with('.') {
+    extension
+  } else {
+    format!(".{extension}")
+  }
+}
+
+#[inline]
+fn mime_type_matches(pattern: &str, mime_type: &str) -> bool {
+  match pattern.strip_suffix("/*") {
+    Some(prefix) => mime_type.split('/').next().is_some_and(|x| x == prefix),
+    None => pattern == mime_type,
+  }
+}
+
+/// Per-crawl overrides of the default `FILE_EXTENSIONS` rejection.
+struct FileTypeRules {
+  include: HashSet<String>,
+  exclude: HashSet<String>,
+}
+
+impl FileTypeRules {
+  fn new(
+    include_extensions: Option<&[String]>,
+    exclude_extensions: Option<&[String]>,
+    include_file_types: Option<&[String]>,
+  ) -> std::result::Result<Self, String> {
+    let mut include: HashSet<String> = include_extensions
+      .unwrap_or_default()
+      .iter()
+      .map(|x| normalize_extension(x))
+      .collect();
+
+    for file_type in include_file_types.unwrap_or_default() {
+      let file_type = file_type.trim().to_lowercase();
+      if file_type.contains('/') {
+        include.extend(
+          FILE_TYPES
+            .iter()
+            .filter(|(_, mime_type, category)| {
+              FILE_TYPE_CATEGORIES.contains(category) && mime_type_matches(&file_type, mime_type)
+            })
+            .map(|(ext, _, _)| ext.to_string()),
+        );
+      } else if FILE_TYPE_CATEGORIES.contains(&file_type.as_str()) {
+        include.extend(
+          FILE_TYPES
+            .iter()
+            .filter(|(_, _, category)| *category == file_type)
+            .map(|(ext, _, _)| ext.to_string()),
+        );
+      } else {
+        return Err(format!("Unknown file type category: {file_type}"));
+      }
+    }
+
+    let exclude = exclude_extensions
+      .unwrap_or_default()
+      .iter()
+      .map(|x| normalize_extension(x))
+      .collect();
+
+    Ok(FileTypeRules { include, exclude })
+  }
+
+  /// The default `FILE_EXTENSIONS` rejection stays case-sensitive, while the
+  /// per-crawl extension lists match any case.
+  #[inline]
+  fn is_denied(&self, path: &str) -> bool {
+    match path_extension(path) {
+      Some(extension) => {
+        let lower = extension.to_lowercase();
+        self.exclude.contains(&lower) || (!self.include.contains(&lower) && is_file(extension))
+      }
+      None => false,
+    }
+  }
+}
+
 #[inline]
 fn get_url_depth(path: &str) -> u32 {
   path
@@ -156,37 +541,111 @@ fn is_internal_link(url: &Url, base_url: &Url) -> bool {
 }
 
 #[inline]
-fn no_sections(url_str: &str) -> bool {
-  if !url_str.contains('#') {
-    return true;
+fn is_hash_route(fragment: &str) -> bool {
+  fragment.len() > 1 && fragment.contains('/')
+}
+
+impl DepthStrategy {
+  /// Path depth plus hash-route segments and level query params, as configured.
+  fn depth(&self, url: &Url) -> u32 {
+    let mut depth = get_url_depth(url.path());
+
+    if self.hash_routes == Some(true) {
+      if let Some(fragment) = url.fragment().filter(|x| is_hash_route(x)) {
+        let route = fragment.split('?').next().unwrap_or("");
+        depth += get_url_depth(route);
+      }
+    }
+
+    if let Some(ref params) = self.query_params_as_levels {
+      let present: HashSet<_> = url.query_pairs().map(|(key, _)| key).collect();
+      depth += params
+        .iter()
+        .filter(|param| present.contains(param.as_str()))
+        .count() as u32;
+    }
+
+    depth
   }
 
-  // Check if the hash fragment looks like a route (contains forward slashes and has substantial content)
-  if let Some(hash_part) = url_str.split('#').nth(1) {
-    hash_part.len() > 1 && hash_part.contains('/')
-  } else {
-    false
+  #[inline]
+  fn is_section(&self, url: &Url) -> bool {
+    match url.fragment() {
+      Some(fragment) => self.hash_routes == Some(false) || !is_hash_route(fragment),
+      None => false,
+    }
+  }
+
+  #[inline]
+  fn is_ignored_param(&self, pair: &str) -> bool {
+    let key = pair.split('=').next().unwrap_or("");
+    self
+      .ignore_query_params
+      .as_ref()
+      .is_some_and(|params| params.iter().any(|x| x == key))
   }
 }
 
+/// Domains treated as social media (or otherwise not worth crawling) unless
+/// the caller provides its own list.
+const DEFAULT_BLOCKED_DOMAINS: &[&str] = &[
+  "facebook.com",
+  "twitter.com",
+  "linkedin.com",
+  "instagram.com",
+  "pinterest.com",
+  "github.com",
+  "calendly.com",
+  "discord.gg",
+  "discord.com",
+];
+
 #[inline]
-fn is_social_media_or_email(url_str: &str) -> bool {
-  const SOCIAL_MEDIA_OR_EMAIL: &[&str] = &[
-    "facebook.com",
-    "twitter.com",
-    "linkedin.com",
-    "instagram.com",
-    "pinterest.com",
-    "mailto:",
-    "github.com",
-    "calendly.com",
-    "discord.gg",
-    "discord.com",
-  ];
+fn registrable_domain(host: &str) -> String {
+  let host = host.trim().trim_end_matches('.').to_lowercase();
+  psl::domain_str(&host).unwrap_or(&host).to_string()
+}
 
-  SOCIAL_MEDIA_OR_EMAIL
-    .iter()
-    .any(|domain| url_str.contains(domain))
+/// Blocked/allowed domain lists, compared on registrable domain so that
+/// `docs.github.com` matches `github.com` but `notfacebook.company.com`
+/// does not match `facebook.com`.
+struct DomainRules {
+  blocked: HashSet<String>,
+  allowed: HashSet<String>,
+}
+
+impl DomainRules {
+  fn new(blocked: Option<&[String]>, allowed: Option<&[String]>) -> Self {
+    let blocked = match blocked {
+      Some(domains) => domains.iter().map(|x| registrable_domain(x)).collect(),
+      None => DEFAULT_BLOCKED_DOMAINS
+        .iter()
+        .map(|x| x.to_string())
+        .collect(),
+    };
+    let allowed = allowed
+      .unwrap_or_default()
+      .iter()
+      .map(|x| registrable_domain(x))
+      .collect();
+
+    DomainRules { blocked, allowed }
+  }
+
+  #[inline]
+  fn is_social_media_or_email(&self, url: &Url) -> bool {
+    if url.scheme() == "mailto" {
+      return true;
+    }
+
+    match url.host_str() {
+      Some(host) => {
+        let domain = registrable_domain(host);
+        !self.allowed.contains(&domain) && self.blocked.contains(&domain)
+      }
+      None => false,
+    }
+  }
 }
 
 #[inline]
@@ -215,302 +674,769 @@ fn is_external_main_page(url_str: &str) -> bool {
   }
 }
 
-fn _filter_links(data: FilterLinksCall) -> std::result::Result<FilterLinksResult, String> {
-  let limit = data.limit.map_or(usize::MAX, |x| x.max(0) as usize);
-  if limit == 0 {
-    return Ok(FilterLinksResult {
-      links: Vec::new(),
-      denial_reasons: HashMap::new(),
-    });
+/// Uppercase percent-encoding hex digits and decode escaped unreserved
+/// characters, so equivalent encodings compare equal.
+fn normalize_percent_encoding(value: &str) -> String {
+  let bytes = value.as_bytes();
+  let mut out = String::with_capacity(value.len());
+  let mut i = 0;
+
+  while i < bytes.len() {
+    if bytes[i] == b'%'
+      && i + 2 < bytes.len()
+      && bytes[i + 1].is_ascii_hexdigit()
+      && bytes[i + 2].is_ascii_hexdigit()
+    {
+      let hex = &value[i + 1..i + 3];
+      if let Ok(byte) = u8::from_str_radix(hex, 16) {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+          out.push(byte as char);
+        } else {
+          out.push('%');
+          out.push_str(&hex.to_uppercase());
+        }
+        i += 3;
+        continue;
+      }
+    }
+
+    let c = value[i..].chars().next().unwrap_or_default();
+    out.push(c);
+    i += c.len_utf8();
   }
 
-  let base_url = Url::parse(&data.base_url).map_err(|e| format!("Base URL parse error: {e}"))?;
-  let initial_url =
-    Url::parse(&data.initial_url).map_err(|e| format!("Initial URL parse error: {e}"))?;
-  let initial_path = initial_url.path();
+  out
+}
 
-  let excludes_regex: Vec<Regex> = data
-    .excludes
-    .iter()
-    .filter_map(|e| Regex::new(e).ok())
-    .collect();
-  let includes_regex: Vec<Regex> = data
-    .includes
-    .iter()
-    .filter_map(|i| Regex::new(i).ok())
-    .collect();
+#[inline]
+fn is_tracking_param(pair: &str) -> bool {
+  let key = pair.split('=').next().unwrap_or("").to_lowercase();
+  key.starts_with("utm_") || TRACKING_QUERY_PARAMS.contains(&key.as_str())
+}
 
-  let robot = if !data.ignore_robots_txt && !data.robots_txt.is_empty() {
-    Robot::new("FireCrawlAgent", data.robots_txt.as_bytes())
-      .ok()
-      .or_else(|| Robot::new("FirecrawlAgent", data.robots_txt.as_bytes()).ok())
-  } else {
-    None
-  };
+/// Reduce a URL to its canonical form: no fragment (unless it is a hash
+/// route), no `index.html`/`index.php`, no trailing slash, normalized
+/// percent-encoding, no tracking parameters and sorted query parameters.
+/// Scheme/host lowercasing and default port removal are done by `Url`.
+fn canonicalize_url(url: &Url, strategy: &DepthStrategy) -> Url {
+  let mut url = url.clone();
+
+  if strategy.is_section(&url) {
+    url.set_fragment(None);
+  }
 
-  let mut result_links = Vec::new();
-  let mut denial_reasons = HashMap::new();
+  if url.cannot_be_a_base() {
+    return url;
+  }
 
-  for link in data.links {
-    if result_links.len() >= limit {
+  let mut path = normalize_percent_encoding(url.path());
+  for index in ["index.html", "index.htm", "index.php"] {
+    if path.ends_with(&format!("/{index}")) {
+      path.truncate(path.len() - index.len());
       break;
     }
+  }
+  if path.len() > 1 && path.ends_with('/') {
+    path = path.trim_end_matches('/').to_string();
+    if path.is_empty() {
+      path.push('/');
+    }
+  }
+  url.set_path(&path);
 
-    let url = match base_url.join(&link) {
-      Ok(url) => url,
-      Err(_) => {
-        denial_reasons.insert(link, URL_PARSE_ERROR.to_string());
-        continue;
-      }
-    };
-
-    let path = url.path();
-    let url_str = url.as_str();
+  if let Some(query) = url.query() {
+    let mut pairs: Vec<String> = query
+      .split('&')
+      .filter(|pair| {
+        !pair.is_empty() && !is_tracking_param(pair) && !strategy.is_ignored_param(pair)
+      })
+      .map(normalize_percent_encoding)
+      .collect();
+    pairs.sort();
+    let query = pairs.join("&");
+    url.set_query(if query.is_empty() { None } else { Some(&query) });
+  }
 
-    if get_url_depth(path) > data.max_depth {
-      denial_reasons.insert(link, DEPTH_LIMIT.to_string());
-      continue;
-    }
+  url
+}
 
-    if is_file(path) {
-      denial_reasons.insert(link, FILE_TYPE.to_string());
-      continue;
-    }
+/// Normalize a URL to its canonical form for deduplication.
+#[napi]
+pub fn normalize_url(url: String, depth_strategy: Option<DepthStrategy>) -> Result<String> {
+  Url::parse(&url)
+    .map(|x| canonicalize_url(&x, &depth_strategy.unwrap_or_default()).to_string())
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Normalize URL error: {e}")))
+}
 
-    if is_internal_link(&url, &base_url) {
-      // INTERNAL LINKS
-      if !no_sections(url_str) {
-        denial_reasons.insert(link, SECTION_LINK.to_string());
-        continue;
+/// Compile regex patterns, recording the ones that fail in `invalid`.
+fn compile_patterns(patterns: &[String], invalid: &mut HashMap<String, String>) -> Vec<Regex> {
+  patterns
+    .iter()
+    .filter_map(|e| match Regex::new(e) {
+      Ok(regex) => Some(regex),
+      Err(err) => {
+        invalid.insert(e.clone(), err.to_string());
+        None
       }
+    })
+    .collect()
+}
+
+/// Translate a gitignore-style glob into a regex over URL paths. Patterns
+/// containing a `/` other than a trailing one are anchored to the path root,
+/// others match at any depth. A match also covers everything below it, so
+/// `/blog` and `/blog/**` both match `/blog` and `/blog/post`.
+fn glob_to_regex(glob: &str) -> std::result::Result<Regex, String> {
+  let trimmed = glob.trim().trim_end_matches('/');
+  if trimmed.is_empty() {
+    return Err("empty pattern".to_string());
+  }
 
-      if !data.allow_backward_crawling && !path.starts_with(initial_path) {
-        denial_reasons.insert(link, BACKWARD_CRAWLING.to_string());
+  let anchored = trimmed.contains('/');
+  let body = trimmed.trim_start_matches('/');
+  let chars: Vec<char> = body.chars().collect();
+  let mut regex = String::from(if anchored { "^/" } else { "(?:^|/)" });
+  let mut i = 0;
+
+  while i < chars.len() {
+    match chars[i] {
+      '*' if chars.get(i + 1) == Some(&'*') => {
+        let at_start = i == 0 || chars[i - 1] == '/';
+        let at_end = i + 2 == chars.len() || chars[i + 2] == '/';
+        if !(at_start && at_end) {
+          return Err("'**' must be a whole path segment".to_string());
+        }
+        if i + 2 == chars.len() {
+          // a trailing `/**` also matches the directory itself
+          if regex.ends_with('/') {
+            regex.pop();
+          }
+          regex.push_str("(?:/.*)?");
+          i += 2;
+        } else {
+          // `**/` matches zero or more whole segments
+          regex.push_str("(?:[^/]*/)*");
+          i += 3;
+        }
         continue;
       }
+      '*' => regex.push_str("[^/]*"),
+      '?' => regex.push_str("[^/]"),
+      '[' => {
+        let end = chars[i + 1..]
+          .iter()
+          .skip(1)
+          .position(|c| *c == ']')
+          .map(|x| i + 2 + x)
+          .ok_or_else(|| "unclosed character class".to_string())?;
+        let mut class: String = chars[i + 1..end].iter().collect();
+        if let Some(rest) = class.strip_prefix('!') {
+          class = format!("^{rest}");
+        }
+        regex.push('[');
+        regex.push_str(&class.replace('\\', "\\\\"));
+        regex.push(']');
+        i = end;
+      }
+      '\\' => {
+        let escaped = chars
+          .get(i + 1)
+          .ok_or_else(|| "trailing escape".to_string())?;
+        regex.push_str(&regex::escape(&escaped.to_string()));
+        i += 1;
+      }
+      c => regex.push_str(&regex::escape(&c.to_string())),
+    }
+    i += 1;
+  }
 
-      let match_target = if data.regex_on_full_url {
-        url_str
-      } else {
-        path
-      };
+  regex.push_str("(?:/.*)?$");
+  Regex::new(&regex).map_err(|e| e.to_string())
+}
 
-      if !excludes_regex.is_empty() && excludes_regex.iter().any(|r| r.is_match(match_target)) {
-        denial_reasons.insert(link, EXCLUDE_PATTERN.to_string());
-        continue;
-      }
+/// Ordered gitignore-style glob list: the last pattern matching a path decides,
+/// and `!` negates. An include list starting with a negation matches
+/// everything else; exclude lists reject leading negations, since they would
+/// exclude everything else.
+struct GlobList {
+  patterns: Vec<(Regex, bool, String)>,
+}
 
-      if !includes_regex.is_empty() && !includes_regex.iter().any(|r| r.is_match(match_target)) {
-        denial_reasons.insert(link, INCLUDE_PATTERN.to_string());
+impl GlobList {
+  fn compile(
+    globs: &[String],
+    leading_negation: bool,
+    invalid: &mut HashMap<String, String>,
+  ) -> Self {
+    let mut patterns: Vec<(Regex, bool, String)> = vec![];
+    for glob in globs {
+      let (negated, pattern) = match glob.trim().strip_prefix('!') {
+        Some(pattern) => (true, pattern),
+        None => (false, glob.as_str()),
+      };
+      if negated && !leading_negation && patterns.is_empty() {
+        invalid.insert(
+          glob.clone(),
+          "negation must follow a pattern it is an exception to".to_string(),
+        );
         continue;
       }
-
-      if let Some(ref robot) = robot {
-        if !robot.allowed(url_str) {
-          denial_reasons.insert(link, ROBOTS_TXT.to_string());
-          continue;
+      match glob_to_regex(pattern) {
+        Ok(regex) => patterns.push((regex, negated, glob.clone())),
+        Err(err) => {
+          invalid.insert(glob.clone(), err);
         }
       }
+    }
 
-      result_links.push(link);
-    } else {
-      // EXTERNAL LINKS
-      if is_social_media_or_email(url_str) {
-        denial_reasons.insert(link, SOCIAL_MEDIA.to_string());
-        continue;
-      }
-
-      if !excludes_regex.is_empty() && excludes_regex.iter().any(|r| r.is_match(url_str)) {
-        denial_reasons.insert(link, EXCLUDE_PATTERN.to_string());
-        continue;
-      }
+    GlobList { patterns }
+  }
 
-      if is_internal_link(&initial_url, &base_url)
-        && data.allow_external_content_links
-        && !is_external_main_page(url_str)
-      {
-        result_links.push(link);
-        continue;
-      }
+  #[inline]
+  fn is_empty(&self) -> bool {
+    self.patterns.is_empty()
+  }
 
-      if data.allow_subdomains
-        && !is_social_media_or_email(url_str)
-        && is_subdomain(&url, &base_url)
-      {
-        result_links.push(link);
-        continue;
-      }
+  /// Last pattern matching `path`, which decides the outcome.
+  fn last_match(&self, path: &str) -> Option<&(Regex, bool, String)> {
+    self
+      .patterns
+      .iter()
+      .rev()
+      .find(|(regex, _, _)| regex.is_match(path))
+  }
 
-      denial_reasons.insert(link, EXTERNAL_LINK.to_string());
-    }
+  fn is_match(&self, path: &str) -> bool {
+    let default = self
+      .patterns
+      .first()
+      .is_some_and(|(_, negated, _)| *negated);
+    self
+      .last_match(path)
+      .map_or(default, |(_, negated, _)| !negated)
   }
 
-  Ok(FilterLinksResult {
-    links: result_links,
-    denial_reasons,
-  })
+  /// Glob behind `is_match(path)`: the last matching one, or the leading
+  /// negation when nothing matched.
+  fn deciding_glob(&self, path: &str) -> Option<String> {
+    self
+      .last_match(path)
+      .or_else(|| self.patterns.first().filter(|(_, negated, _)| *negated))
+      .map(|(_, _, glob)| glob.clone())
+  }
 }
 
-/// Filter links based on crawling rules and constraints.
-#[napi]
-pub fn filter_links(data: FilterLinksCall) -> Result<FilterLinksResult> {
-  _filter_links(data)
-    .map_err(|e| Error::new(Status::GenericFailure, format!("Filter links error: {e}")))
+/// robots.txt compiled for the preferred user agent. The parsed rules are
+/// kept to explain denials.
+struct RobotsRules {
+  robot: Robot,
+  parsed: RobotsTxt,
+  user_agent: String,
 }
 
-fn _filter_url(data: FilterUrlCall) -> std::result::Result<FilterUrlResult, String> {
-  let mut full_url = data.href.clone();
+impl RobotsRules {
+  #[inline]
+  fn allowed(&self, url: &str) -> bool {
+    self.robot.allowed(url)
+  }
 
-  // Handle relative URLs
-  if !data.href.starts_with("http") {
-    match Url::parse(&data.url) {
-      Ok(base) => match base.join(&data.href) {
-        Ok(resolved) => full_url = resolved.to_string(),
-        Err(_) => {
-          return Ok(FilterUrlResult {
-            allowed: false,
-            url: None,
-            denial_reason: Some(URL_PARSE_ERROR.to_string()),
-          });
-        }
-      },
-      Err(_) => {
-        return Ok(FilterUrlResult {
-          allowed: false,
-          url: None,
-          denial_reason: Some(URL_PARSE_ERROR.to_string()),
-        });
-      }
-    }
+  fn matched_rule(&self, url: &Url) -> Option<RobotsTxtRule> {
+    self
+      .parsed
+      .rules_for(&self.user_agent)
+      .matched_rule(url)
+      .cloned()
   }
+}
 
-  let url = match Url::parse(&full_url) {
-    Ok(url) => url,
-    Err(_) => {
-      return Ok(FilterUrlResult {
-        allowed: false,
-        url: None,
-        denial_reason: Some(URL_PARSE_ERROR.to_string()),
-      });
-    }
-  };
+fn compile_robot(
+  ignore_robots_txt: bool,
+  robots_txt: &str,
+  user_agents: Option<&[String]>,
+) -> Option<RobotsRules> {
+  if ignore_robots_txt || robots_txt.is_empty() {
+    return None;
+  }
 
-  let base_url = match Url::parse(&data.base_url) {
-    Ok(url) => url,
-    Err(_) => {
-      return Ok(FilterUrlResult {
-        allowed: false,
-        url: None,
-        denial_reason: Some(URL_PARSE_ERROR.to_string()),
-      });
-    }
+  let user_agents = match user_agents {
+    Some(x) if !x.is_empty() => x.to_vec(),
+    _ => DEFAULT_ROBOTS_USER_AGENTS
+      .iter()
+      .map(|x| x.to_string())
+      .collect(),
   };
+  let parsed = RobotsTxt::parse(robots_txt);
+  let user_agent = parsed.preferred_user_agent(&user_agents).to_string();
+
+  Robot::new(&user_agent, robots_txt.as_bytes())
+    .ok()
+    .map(|robot| RobotsRules {
+      robot,
+      parsed,
+      user_agent,
+    })
+}
 
-  let path = url.path();
-  let url_str = url.as_str();
-
-  let excludes_regex: Vec<Regex> = data
-    .excludes
-    .iter()
-    .filter_map(|e| Regex::new(e).ok())
-    .collect();
+#[inline]
+fn denied_url(reason: &str) -> FilterUrlResult {
+  FilterUrlResult {
+    allowed: false,
+    url: None,
+    denial_reason: Some(reason.to_string()),
+    invalid_patterns: HashMap::new(),
+  }
+}
 
-  let robot = if !data.ignore_robots_txt && !data.robots_txt.is_empty() {
-    Robot::new("FireCrawlAgent", data.robots_txt.as_bytes())
-      .ok()
-      .or_else(|| Robot::new("FirecrawlAgent", data.robots_txt.as_bytes()).ok())
-  } else {
-    None
-  };
+/// Rules shared by link batches and single-URL filtering.
+struct UrlRules {
+  base_url: Url,
+  excludes_regex: Vec<Regex>,
+  include_globs: GlobList,
+  exclude_globs: GlobList,
+  depth_strategy: DepthStrategy,
+  robot: Option<RobotsRules>,
+  allow_external_content_links: bool,
+  allow_subdomains: bool,
+  domains: DomainRules,
+}
 
-  if is_internal_link(&url, &base_url) {
-    // INTERNAL LINKS
-    if !no_sections(url_str) {
-      return Ok(FilterUrlResult {
-        allowed: false,
-        url: None,
-        denial_reason: Some(SECTION_LINK.to_string()),
-      });
-    }
+impl UrlRules {
+  fn compile(
+    options: UrlRuleOptions,
+    invalid_patterns: &mut HashMap<String, String>,
+  ) -> std::result::Result<Self, url::ParseError> {
+    let base_url = Url::parse(&options.base_url)?;
+
+    Ok(UrlRules {
+      base_url,
+      excludes_regex: compile_patterns(&options.excludes, invalid_patterns),
+      include_globs: GlobList::compile(
+        options.include_globs.as_deref().unwrap_or_default(),
+        true,
+        invalid_patterns,
+      ),
+      exclude_globs: GlobList::compile(
+        options.exclude_globs.as_deref().unwrap_or_default(),
+        false,
+        invalid_patterns,
+      ),
+      depth_strategy: options.depth_strategy.unwrap_or_default(),
+      robot: compile_robot(
+        options.ignore_robots_txt,
+        &options.robots_txt,
+        options.user_agents.as_deref(),
+      ),
+      allow_external_content_links: options.allow_external_content_links,
+      allow_subdomains: options.allow_subdomains,
+      domains: DomainRules::new(
+        options.blocked_domains.as_deref(),
+        options.allowed_domains.as_deref(),
+      ),
+    })
+  }
 
-    if !excludes_regex.is_empty() && excludes_regex.iter().any(|r| r.is_match(path)) {
-      return Ok(FilterUrlResult {
-        allowed: false,
-        url: None,
-        denial_reason: Some(EXCLUDE_PATTERN.to_string()),
-      });
-    }
+  fn filter_url(&self, href: &str, context: &str) -> FilterUrlResult {
+    let mut full_url = href.to_string();
 
-    if let Some(ref robot) = robot {
-      if !robot.allowed(url_str) {
-        return Ok(FilterUrlResult {
-          allowed: false,
-          url: None,
-          denial_reason: Some(ROBOTS_TXT.to_string()),
-        });
+    // Handle relative URLs
+    if !href.starts_with("http") {
+      match Url::parse(context).and_then(|base| base.join(href)) {
+        Ok(resolved) => full_url = resolved.to_string(),
+        Err(_) => return denied_url(URL_PARSE_ERROR),
       }
     }
 
-    Ok(FilterUrlResult {
-      allowed: true,
-      url: Some(full_url),
-      denial_reason: None,
+    let url = match Url::parse(&full_url) {
+      Ok(url) => url,
+      Err(_) => return denied_url(URL_PARSE_ERROR),
+    };
+
+    let path = url.path();
+    let url_str = url.as_str();
+
+    if is_internal_link(&url, &self.base_url) {
+      // INTERNAL LINKS
+      if self.depth_strategy.is_section(&url) {
+        return denied_url(SECTION_LINK);
+      }
+
+      if self.excludes_regex.iter().any(|r| r.is_match(path)) || self.exclude_globs.is_match(path) {
+        return denied_url(EXCLUDE_PATTERN);
+      }
+
+      if !self.include_globs.is_empty() && !self.include_globs.is_match(path) {
+        return denied_url(INCLUDE_PATTERN);
+      }
+
+      if let Some(ref robot) = self.robot {
+        if !robot.allowed(url_str) {
+          return denied_url(ROBOTS_TXT);
+        }
+      }
+    } else {
+      // EXTERNAL LINKS
+      if self.domains.is_social_media_or_email(&url) {
+        return denied_url(SOCIAL_MEDIA);
+      }
+
+      if self.excludes_regex.iter().any(|r| r.is_match(url_str)) {
+        return denied_url(EXCLUDE_PATTERN);
+      }
+
+      let context_url = match Url::parse(context) {
+        Ok(url) => url,
+        Err(_) => return denied_url(URL_PARSE_ERROR),
+      };
+
+      let allowed = (is_internal_link(&context_url, &self.base_url)
+        && self.allow_external_content_links
+        && !is_external_main_page(url_str))
+        || (self.allow_subdomains && is_subdomain(&url, &self.base_url));
+
+      if !allowed {
+        return denied_url(EXTERNAL_LINK);
+      }
+    }
+
+    FilterUrlResult {
+      allowed: true,
+      url: Some(full_url),
+      denial_reason: None,
+      invalid_patterns: HashMap::new(),
+    }
+  }
+}
+
+/// Crawl filter compiled once from `LinkFilterOptions` and reused for every
+/// link batch, so patterns and robots.txt are not re-parsed per call.
+#[napi]
+pub struct LinkFilter {
+  rules: UrlRules,
+  limit: usize,
+  max_depth: u32,
+  initial_url: Url,
+  regex_on_full_url: bool,
+  includes_regex: Vec<Regex>,
+  invalid_patterns: HashMap<String, String>,
+  allow_backward_crawling: bool,
+  file_types: FileTypeRules,
+  explain_denials: bool,
+  deduplicate: bool,
+}
+
+impl LinkFilter {
+  pub(crate) fn compile(options: LinkFilterOptions) -> std::result::Result<Self, String> {
+    let mut invalid_patterns = HashMap::new();
+    let rules = UrlRules::compile(options.rules, &mut invalid_patterns)
+      .map_err(|e| format!("Base URL parse error: {e}"))?;
+    let initial_url =
+      Url::parse(&options.initial_url).map_err(|e| format!("Initial URL parse error: {e}"))?;
+    let includes_regex = compile_patterns(&options.includes, &mut invalid_patterns);
+
+    Ok(LinkFilter {
+      rules,
+      limit: options.limit.map_or(usize::MAX, |x| x.max(0) as usize),
+      max_depth: options.max_depth,
+      initial_url,
+      regex_on_full_url: options.regex_on_full_url,
+      includes_regex,
+      invalid_patterns,
+      allow_backward_crawling: options.allow_backward_crawling,
+      file_types: FileTypeRules::new(
+        options.include_extensions.as_deref(),
+        options.exclude_extensions.as_deref(),
+        options.include_file_types.as_deref(),
+      )?,
+      explain_denials: options.explain_denials.unwrap_or(false),
+      deduplicate: options.deduplicate.unwrap_or(false),
     })
-  } else {
-    // EXTERNAL LINKS
-    if is_social_media_or_email(url_str) {
-      return Ok(FilterUrlResult {
-        allowed: false,
-        url: None,
-        denial_reason: Some(SOCIAL_MEDIA.to_string()),
+  }
+
+  /// Crawl-delay robots.txt sets for the user agent links are checked as.
+  pub(crate) fn robots_crawl_delay(&self) -> Option<f64> {
+    self
+      .rules
+      .robot
+      .as_ref()
+      .and_then(|robot| robot.parsed.rules_for(&robot.user_agent).crawl_delay)
+  }
+
+  /// Canonical form `link` is deduplicated under, resolved against `base_url`.
+  pub(crate) fn canonical_link(&self, link: &str) -> Option<String> {
+    let url = self.rules.base_url.join(link).ok()?;
+    Some(canonicalize_url(&url, &self.rules.depth_strategy).to_string())
+  }
+
+  /// Run every crawl rule against a resolved link, describing the first rule
+  /// it fails.
+  fn check_link(&self, url: &Url) -> Option<LinkDenial> {
+    let base_url = &self.rules.base_url;
+    let path = url.path();
+    let url_str = url.as_str();
+    let deny = |reason: &str| LinkDenial {
+      reason: reason.to_string(),
+      resolved_url: Some(url_str.to_string()),
+      ..Default::default()
+    };
+
+    let depth = self.rules.depth_strategy.depth(url);
+    if depth > self.max_depth {
+      return Some(LinkDenial {
+        depth: Some(depth),
+        max_depth: Some(self.max_depth),
+        ..deny(DEPTH_LIMIT)
       });
     }
 
-    if !excludes_regex.is_empty() && excludes_regex.iter().any(|r| r.is_match(url_str)) {
-      return Ok(FilterUrlResult {
-        allowed: false,
-        url: None,
-        denial_reason: Some(EXCLUDE_PATTERN.to_string()),
+    if self.file_types.is_denied(path) {
+      return Some(LinkDenial {
+        extension: path_extension(path).map(|x| x.to_string()),
+        ..deny(FILE_TYPE)
       });
     }
 
-    let context_url = match Url::parse(&data.url) {
-      Ok(url) => url,
-      Err(_) => {
-        return Ok(FilterUrlResult {
-          allowed: false,
-          url: None,
-          denial_reason: Some(URL_PARSE_ERROR.to_string()),
+    if is_internal_link(url, base_url) {
+      // INTERNAL LINKS
+      if self.rules.depth_strategy.is_section(url) {
+        return Some(deny(SECTION_LINK));
+      }
+
+      if !self.allow_backward_crawling && !path.starts_with(self.initial_url.path()) {
+        return Some(deny(BACKWARD_CRAWLING));
+      }
+
+      let match_target = if self.regex_on_full_url {
+        url_str
+      } else {
+        path
+      };
+
+      if let Some(regex) = self
+        .rules
+        .excludes_regex
+        .iter()
+        .find(|r| r.is_match(match_target))
+      {
+        return Some(LinkDenial {
+          pattern: Some(regex.as_str().to_string()),
+          ..deny(EXCLUDE_PATTERN)
+        });
+      }
+
+      if self.rules.exclude_globs.is_match(path) {
+        return Some(LinkDenial {
+          pattern: self.rules.exclude_globs.deciding_glob(path),
+          ..deny(EXCLUDE_PATTERN)
+        });
+      }
+
+      if !self.includes_regex.is_empty()
+        && !self.includes_regex.iter().any(|r| r.is_match(match_target))
+      {
+        return Some(deny(INCLUDE_PATTERN));
+      }
+
+      if !self.rules.include_globs.is_empty() && !self.rules.include_globs.is_match(path) {
+        return Some(LinkDenial {
+          pattern: self.rules.include_globs.deciding_glob(path),
+          ..deny(INCLUDE_PATTERN)
+        });
+      }
+
+      if let Some(ref robot) = self.rules.robot {
+        if !robot.allowed(url_str) {
+          return Some(LinkDenial {
+            robots_txt_rule: robot.matched_rule(url),
+            robots_user_agent: Some(robot.user_agent.clone()),
+            ..deny(ROBOTS_TXT)
+          });
+        }
+      }
+
+      None
+    } else {
+      // EXTERNAL LINKS
+      let domain = url.host_str().map(registrable_domain);
+
+      if self.rules.domains.is_social_media_or_email(url) {
+        return Some(LinkDenial {
+          domain,
+          ..deny(SOCIAL_MEDIA)
+        });
+      }
+
+      if let Some(regex) = self
+        .rules
+        .excludes_regex
+        .iter()
+        .find(|r| r.is_match(url_str))
+      {
+        return Some(LinkDenial {
+          pattern: Some(regex.as_str().to_string()),
+          ..deny(EXCLUDE_PATTERN)
         });
       }
+
+      if is_internal_link(&self.initial_url, base_url)
+        && self.rules.allow_external_content_links
+        && !is_external_main_page(url_str)
+      {
+        return None;
+      }
+
+      if self.rules.allow_subdomains && is_subdomain(url, base_url) {
+        return None;
+      }
+
+      Some(LinkDenial {
+        domain,
+        ..deny(EXTERNAL_LINK)
+      })
+    }
+  }
+
+  /// Filter `links`, accepting at most `limit`. Given a `seen` set, links are
+  /// always deduplicated: canonical URLs already in it are denied and accepted
+  /// ones are added to it.
+  pub(crate) fn filter_batch(
+    &self,
+    links: Vec<String>,
+    limit: usize,
+    seen: Option<&mut SeenSet>,
+  ) -> FilterLinksResult {
+    let mut batch_seen = SeenSet::default();
+    let (seen, deduplicate) = match seen {
+      Some(seen) => (seen, true),
+      None => (&mut batch_seen, self.deduplicate),
     };
+    let mut result_links = Vec::new();
+    let mut denial_reasons = HashMap::new();
+    let mut denial_details = HashMap::new();
 
-    if is_internal_link(&context_url, &base_url)
-      && data.allow_external_content_links
-      && !is_external_main_page(url_str)
-    {
-      return Ok(FilterUrlResult {
-        allowed: true,
-        url: Some(full_url),
-        denial_reason: None,
-      });
+    for link in links {
+      if result_links.len() >= limit {
+        break;
+      }
+
+      let denial = match self.rules.base_url.join(&link) {
+        Err(_) => Some(LinkDenial {
+          reason: URL_PARSE_ERROR.to_string(),
+          ..Default::default()
+        }),
+        Ok(url) => match self.check_link(&url) {
+          Some(denial) => Some(denial),
+          None if deduplicate => {
+            let canonical = canonicalize_url(&url, &self.rules.depth_strategy).to_string();
+            if seen.insert(&canonical) {
+              result_links.push(canonical);
+              continue;
+            }
+            Some(LinkDenial {
+              reason: DUPLICATE.to_string(),
+              resolved_url: Some(url.to_string()),
+              duplicate_of: Some(canonical),
+              ..Default::default()
+            })
+          }
+          None => {
+            result_links.push(link);
+            continue;
+          }
+        },
+      };
+
+      if let Some(denial) = denial {
+        denial_reasons.insert(link.clone(), denial.reason.clone());
+        if self.explain_denials {
+          denial_details.insert(link, denial);
+        }
+      }
     }
 
-    if data.allow_subdomains && !is_social_media_or_email(url_str) && is_subdomain(&url, &base_url)
-    {
-      return Ok(FilterUrlResult {
-        allowed: true,
-        url: Some(full_url),
-        denial_reason: None,
-      });
+    FilterLinksResult {
+      links: result_links,
+      denial_reasons,
+      denial_details: self.explain_denials.then_some(denial_details),
+      invalid_patterns: self.invalid_patterns.clone(),
+    }
+  }
+}
+
+#[napi]
+impl LinkFilter {
+  #[napi(constructor)]
+  pub fn new(options: LinkFilterOptions) -> Result<Self> {
+    LinkFilter::compile(options)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Link filter error: {e}")))
+  }
+
+  /// Filter a batch of links based on crawling rules and constraints.
+  #[napi]
+  pub fn filter_links(&self, links: Vec<String>) -> FilterLinksResult {
+    self.filter_batch(links, self.limit, None)
+  }
+
+  /// Like `filter_links`, but also denies links already in `seen` as
+  /// `DUPLICATE` and adds the accepted ones to it.
+  #[napi]
+  pub fn filter_unseen_links(&self, links: Vec<String>, seen: &mut SeenSet) -> FilterLinksResult {
+    self.filter_batch(links, self.limit, Some(seen))
+  }
+
+  /// Filter a single URL found on the page at `url`.
+  #[napi]
+  pub fn filter_url(&self, href: String, url: String) -> FilterUrlResult {
+    FilterUrlResult {
+      invalid_patterns: self.invalid_patterns.clone(),
+      ..self.rules.filter_url(&href, &url)
     }
+  }
+}
 
-    Ok(FilterUrlResult {
-      allowed: false,
-      url: None,
-      denial_reason: Some(EXTERNAL_LINK.to_string()),
-    })
+fn _filter_links(
+  data: FilterLinksCall,
+  seen: Option<&mut SeenSet>,
+) -> std::result::Result<FilterLinksResult, String> {
+  let FilterLinksCall { links, options } = data;
+  if options.limit.is_some_and(|x| x <= 0) {
+    return Ok(FilterLinksResult {
+      links: Vec::new(),
+      denial_reasons: HashMap::new(),
+      denial_details: None,
+      invalid_patterns: HashMap::new(),
+    });
   }
+
+  let filter = LinkFilter::compile(options)?;
+  Ok(filter.filter_batch(links, filter.limit, seen))
+}
+
+/// Filter links based on crawling rules and constraints.
+#[napi]
+pub fn filter_links(data: FilterLinksCall) -> Result<FilterLinksResult> {
+  _filter_links(data, None)
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Filter links error: {e}")))
+}
+
+/// Filter links like `filter_links`, dropping the ones already in `seen` and
+/// adding the accepted ones to it.
+#[napi]
+pub fn filter_unseen_links(data: FilterLinksCall, seen: &mut SeenSet) -> Result<FilterLinksResult> {
+  _filter_links(data, Some(seen))
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Filter links error: {e}")))
+}
+
+fn _filter_url(data: FilterUrlCall) -> std::result::Result<FilterUrlResult, String> {
+  let mut invalid_patterns = HashMap::new();
+  let rules = match UrlRules::compile(data.rules, &mut invalid_patterns) {
+    Ok(rules) => rules,
+    Err(_) => return Ok(denied_url(URL_PARSE_ERROR)),
+  };
+
+  Ok(FilterUrlResult {
+    invalid_patterns,
+    ..rules.filter_url(&data.href, &data.url)
+  })
 }
 
 /// Filter a single URL based on crawling rules and constraints.
@@ -520,7 +1446,270 @@ pub fn filter_url(data: FilterUrlCall) -> Result<FilterUrlResult> {
     .map_err(|e| Error::new(Status::GenericFailure, format!("Filter URL error: {e}")))
 }
 
-fn _parse_sitemap_xml(xml_content: &str) -> std::result::Result<ParsedSitemap, String> {
+#[inline]
+fn sitemap_child_text(node: &roxmltree::Node, name: &str) -> Option<String> {
+  node
+    .children()
+    .find(|n| n.is_element() && n.tag_name().name() == name)
+    .and_then(|n| n.text())
+    .map(|text| text.to_string())
+}
+
+#[inline]
+fn sitemap_children<'a, 'input>(
+  node: &roxmltree::Node<'a, 'input>,
+  name: &'static str,
+) -> impl Iterator<Item = roxmltree::Node<'a, 'input>> {
+  node
+    .children()
+    .filter(move |n| n.is_element() && n.tag_name().name() == name)
+}
+
+#[inline]
+fn non_empty<T>(items: Vec<T>) -> Option<Vec<T>> {
+  if items.is_empty() {
+    None
+  } else {
+    Some(items)
+  }
+}
+
+fn parse_sitemap_images(url_node: &roxmltree::Node) -> Vec<SitemapImage> {
+  sitemap_children(url_node, "image")
+    .filter_map(|image| {
+      sitemap_child_text(&image, "loc").map(|loc| SitemapImage {
+        loc: loc.trim().to_string(),
+        caption: sitemap_child_text(&image, "caption"),
+        title: sitemap_child_text(&image, "title"),
+        geo_location: sitemap_child_text(&image, "geo_location"),
+        license: sitemap_child_text(&image, "license"),
+      })
+    })
+    .collect()
+}
+
+fn parse_sitemap_videos(url_node: &roxmltree::Node) -> Vec<SitemapVideo> {
+  sitemap_children(url_node, "video")
+    .map(|video| SitemapVideo {
+      thumbnail_loc: sitemap_child_text(&video, "thumbnail_loc").map(|x| x.trim().to_string()),
+      title: sitemap_child_text(&video, "title"),
+      description: sitemap_child_text(&video, "description"),
+      content_loc: sitemap_child_text(&video, "content_loc").map(|x| x.trim().to_string()),
+      player_loc: sitemap_child_text(&video, "player_loc").map(|x| x.trim().to_string()),
+      duration: sitemap_child_text(&video, "duration").and_then(|x| x.trim().parse().ok()),
+      publication_date: sitemap_child_text(&video, "publication_date"),
+      expiration_date: sitemap_child_text(&video, "expiration_date"),
+      family_friendly: sitemap_child_text(&video, "family_friendly")
+        .map(|x| !x.trim().eq_ignore_ascii_case("no")),
+      tags: non_empty(
+        sitemap_children(&video, "tag")
+          .filter_map(|tag| tag.text().map(|x| x.to_string()))
+          .collect(),
+      ),
+    })
+    .collect()
+}
+
+fn parse_sitemap_news(url_node: &roxmltree::Node) -> Option<SitemapNews> {
+  sitemap_children(url_node, "news").next().map(|news| {
+    let publication = sitemap_children(&news, "publication").next();
+    SitemapNews {
+      publication_name: publication.and_then(|x| sitemap_child_text(&x, "name")),
+      publication_language: publication.and_then(|x| sitemap_child_text(&x, "language")),
+      publication_date: sitemap_child_text(&news, "publication_date"),
+      title: sitemap_child_text(&news, "title"),
+      keywords: sitemap_child_text(&news, "keywords"),
+    }
+  })
+}
+
+fn parse_sitemap_alternates(url_node: &roxmltree::Node) -> Vec<SitemapAlternate> {
+  sitemap_children(url_node, "link")
+    .filter(|link| link.attribute("rel") == Some("alternate"))
+    .filter_map(|link| {
+      Some(SitemapAlternate {
+        hreflang: link.attribute("hreflang")?.trim().to_string(),
+        href: link.attribute("href")?.trim().to_string(),
+      })
+    })
+    .collect()
+}
+
+/// Resolve a feed link against the `xml:base` attributes in scope and the URL
+/// the feed was fetched from. Links that can't be resolved are kept as-is.
+fn feed_link_url(node: &roxmltree::Node, href: &str, base_url: Option<&Url>) -> String {
+  let href = href.trim();
+  let xml_bases: Vec<&str> = node
+    .ancestors()
+    .filter_map(|n| n.attribute((roxmltree::NS_XML_URI, "base")))
+    .collect();
+  let base = xml_bases
+    .into_iter()
+    .rev()
+    .fold(base_url.cloned(), |base, xml_base| {
+      match &base {
+        Some(base) => base.join(xml_base),
+        None => Url::parse(xml_base),
+      }
+      .ok()
+      .or(base)
+    });
+
+  match base {
+    Some(base) => base.join(href),
+    None => Url::parse(href),
+  }
+  .map_or_else(|_| href.to_string(), String::from)
+}
+
+#[inline]
+fn feed_sitemap_url(loc: String, lastmod: Option<String>) -> SitemapUrl {
+  SitemapUrl {
+    loc: vec![loc],
+    lastmod,
+    changefreq: None,
+    priority: None,
+    images: None,
+    videos: None,
+    news: None,
+    alternates: None,
+  }
+}
+
+/// Convert an RFC 822 date (RSS `<pubDate>`) into a W3C datetime.
+fn rfc822_to_w3c(value: &str) -> Option<String> {
+  const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+  ];
+
+  let value = value.trim();
+  let value = value.split_once(',').map_or(value, |(_, rest)| rest);
+  let mut parts = value.split_whitespace();
+
+  let day: u32 = parts.next()?.parse().ok()?;
+  let month_name = parts.next()?.to_lowercase();
+  let month = MONTHS.iter().position(|m| month_name.starts_with(m))? + 1;
+  let year: i64 = match parts.next()?.parse().ok()? {
+    x @ 0..=49 => x + 2000,
+    x @ 50..=99 => x + 1900,
+    x => x,
+  };
+  let time = parts.next().unwrap_or("00:00:00");
+  let time = if time.matches(':').count() == 1 {
+    format!("{time}:00")
+  } else {
+    time.to_string()
+  };
+
+  let offset = match parts.next().unwrap_or("GMT").to_uppercase().as_str() {
+    "GMT" | "UT" | "UTC" | "Z" => "+00:00".to_string(),
+    "EST" => "-05:00".to_string(),
+    "EDT" => "-04:00".to_string(),
+    "CST" => "-06:00".to_string(),
+    "CDT" => "-05:00".to_string(),
+    "MST" => "-07:00".to_string(),
+    "MDT" => "-06:00".to_string(),
+    "PST" => "-08:00".to_string(),
+    "PDT" => "-07:00".to_string(),
+    x if x.len() == 5 && (x.starts_with('+') || x.starts_with('-')) => {
+      format!("{}:{}", &x[..3], &x[3..])
+    }
+    _ => return None,
+  };
+
+  Some(format!("{year:04}-{month:02}-{day:02}T{time}{offset}"))
+}
+
+/// Number of days in `month` of `year`, in the proleptic Gregorian calendar.
+fn days_in_month(year: i64, month: i64) -> i64 {
+  match month {
+    2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+    2 => 28,
+    4 | 6 | 9 | 11 => 30,
+    _ => 31,
+  }
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+  let year = if month <= 2 { year - 1 } else { year };
+  let era = year.div_euclid(400);
+  let yoe = year - era * 400;
+  let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+  let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+  era * 146097 + doe - 719468
+}
+
+/// Parse a W3C datetime (the format used by sitemap `<lastmod>`) into Unix
+/// seconds. Accepts `YYYY`, `YYYY-MM`, `YYYY-MM-DD` and full timestamps with
+/// optional fractional seconds and a `Z`, `±hh:mm`, `±hhmm` or `±hh` offset.
+fn parse_w3c_datetime(value: &str) -> Option<i64> {
+  let value = value.trim();
+  let (date, time) = match value.split_once(['T', ' ']) {
+    Some((date, time)) => (date, Some(time)),
+    None => (value, None),
+  };
+
+  let mut date_parts = date.split('-');
+  let year: i64 = date_parts.next()?.parse().ok()?;
+  let month: i64 = date_parts.next().map_or(Some(1), |x| x.parse().ok())?;
+  let day: i64 = date_parts.next().map_or(Some(1), |x| x.parse().ok())?;
+  if date_parts.next().is_some()
+    || !(1..=12).contains(&month)
+    || !(1..=days_in_month(year, month)).contains(&day)
+  {
+    return None;
+  }
+
+  let mut seconds = days_from_civil(year, month, day) * 86400;
+
+  if let Some(time) = time {
+    let (clock, offset) = if let Some(clock) = time.strip_suffix(['Z', 'z']) {
+      (clock, 0)
+    } else if let Some(pos) = time.rfind(['+', '-']) {
+      let (clock, offset) = time.split_at(pos);
+      let sign = if offset.starts_with('-') { -1 } else { 1 };
+      let offset = &offset[1..];
+      let (hours, minutes) = match offset.split_once(':') {
+        Some(x) => x,
+        None if offset.len() == 4 => offset.split_at(2),
+        None if offset.len() == 2 => (offset, "0"),
+        None => return None,
+      };
+      let (hours, minutes): (i64, i64) = (hours.parse().ok()?, minutes.parse().ok()?);
+      if hours > 23 || minutes > 59 {
+        return None;
+      }
+      let offset = hours * 3600 + minutes * 60;
+      (clock, sign * offset)
+    } else {
+      (time, 0)
+    };
+
+    let mut clock_parts = clock.split(':');
+    let hours: i64 = clock_parts.next()?.parse().ok()?;
+    let minutes: i64 = clock_parts.next().map_or(Some(0), |x| x.parse().ok())?;
+    let secs: f64 = clock_parts.next().map_or(Some(0.0), |x| x.parse().ok())?;
+    seconds += hours * 3600 + minutes * 60 + secs as i64 - offset;
+  }
+
+  Some(seconds)
+}
+
+#[inline]
+fn sitemap_priority(priority: Option<f64>) -> f64 {
+  priority.unwrap_or(0.5)
+}
+
+#[inline]
+fn sitemap_lastmod(lastmod: &Option<String>) -> Option<i64> {
+  lastmod.as_deref().and_then(parse_w3c_datetime)
+}
+
+fn _parse_sitemap_xml(
+  xml_content: &str,
+  base_url: Option<&Url>,
+) -> std::result::Result<ParsedSitemap, String> {
   let doc = roxmltree::Document::parse_with_options(
     xml_content,
     roxmltree::ParsingOptions {
@@ -537,13 +1726,10 @@ fn _parse_sitemap_xml(xml_content: &str) -> std::result::Result<ParsedSitemap, S
         .children()
         .filter(|n| n.is_element() && n.tag_name().name() == "sitemap")
         .filter_map(|sitemap_node| {
-          sitemap_node
-            .children()
-            .find(|n| n.is_element() && n.tag_name().name() == "loc")
-            .and_then(|loc_node| loc_node.text())
-            .map(|loc_text| SitemapEntry {
-              loc: vec![loc_text.to_string()],
-            })
+          sitemap_child_text(&sitemap_node, "loc").map(|loc_text| SitemapEntry {
+            loc: vec![loc_text],
+            lastmod: sitemap_child_text(&sitemap_node, "lastmod"),
+          })
         })
         .collect();
 
@@ -557,13 +1743,64 @@ fn _parse_sitemap_xml(xml_content: &str) -> std::result::Result<ParsedSitemap, S
         .children()
         .filter(|n| n.is_element() && n.tag_name().name() == "url")
         .filter_map(|url_node| {
-          url_node
-            .children()
-            .find(|n| n.is_element() && n.tag_name().name() == "loc")
-            .and_then(|loc_node| loc_node.text())
-            .map(|loc_text| SitemapUrl {
-              loc: vec![loc_text.to_string()],
-            })
+          sitemap_child_text(&url_node, "loc").map(|loc_text| SitemapUrl {
+            loc: vec![loc_text],
+            lastmod: sitemap_child_text(&url_node, "lastmod"),
+            changefreq: sitemap_child_text(&url_node, "changefreq"),
+            priority: sitemap_child_text(&url_node, "priority").and_then(|x| x.trim().parse().ok()),
+            images: non_empty(parse_sitemap_images(&url_node)),
+            videos: non_empty(parse_sitemap_videos(&url_node)),
+            news: parse_sitemap_news(&url_node),
+            alternates: non_empty(parse_sitemap_alternates(&url_node)),
+          })
+        })
+        .collect();
+
+      Ok(ParsedSitemap {
+        urlset: Some(SitemapUrlset { url: urls }),
+        sitemapindex: None,
+      })
+    }
+    "rss" | "RDF" => {
+      // RSS 2.0 nests items in <channel>, RSS 1.0 (RDF) keeps them at the root.
+      let channel = sitemap_children(&root, "channel").next().unwrap_or(root);
+      let urls = sitemap_children(&channel, "item")
+        .chain(sitemap_children(&root, "item").filter(|_| channel != root))
+        .filter_map(|item| {
+          let loc = sitemap_children(&item, "link")
+            .next()
+            .and_then(|link| Some(feed_link_url(&link, link.text()?, base_url)))
+            .or_else(|| {
+              sitemap_children(&item, "guid")
+                .find(|guid| guid.attribute("isPermaLink") != Some("false"))
+                .and_then(|guid| guid.text().map(|x| x.to_string()))
+            })?;
+          let lastmod = sitemap_child_text(&item, "pubDate")
+            .and_then(|x| rfc822_to_w3c(&x))
+            .or_else(|| sitemap_child_text(&item, "date"));
+          Some(feed_sitemap_url(loc, lastmod))
+        })
+        .collect();
+
+      Ok(ParsedSitemap {
+        urlset: Some(SitemapUrlset { url: urls }),
+        sitemapindex: None,
+      })
+    }
+    "feed" => {
+      let urls = sitemap_children(&root, "entry")
+        .filter_map(|entry| {
+          let links: Vec<_> = sitemap_children(&entry, "link")
+            .filter(|link| link.attribute("rel").is_none_or(|rel| rel == "alternate"))
+            .collect();
+          let link = links
+            .iter()
+            .find(|link| link.attribute("type").is_none_or(|t| t.contains("html")))
+            .or(links.first())?;
+          let loc = feed_link_url(link, link.attribute("href")?, base_url);
+          let lastmod = sitemap_child_text(&entry, "updated")
+            .or_else(|| sitemap_child_text(&entry, "published"));
+          Some(feed_sitemap_url(loc, lastmod))
         })
         .collect();
 
@@ -572,14 +1809,52 @@ fn _parse_sitemap_xml(xml_content: &str) -> std::result::Result<ParsedSitemap, S
         sitemapindex: None,
       })
     }
-    _ => Err("Invalid sitemap format: root element must be 'sitemapindex' or 'urlset'".to_string()),
+    _ => Err(
+      "Invalid sitemap format: root element must be 'sitemapindex', 'urlset', 'rss' or 'feed'"
+        .to_string(),
+    ),
+  }
+}
+
+/// Parse a plain-text sitemap (one absolute URL per line).
+fn _parse_sitemap_text(content: &str) -> std::result::Result<ParsedSitemap, String> {
+  let urls: Vec<SitemapUrl> = content
+    .lines()
+    .map(str::trim)
+    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+    .filter(|line| Url::parse(line).is_ok_and(|x| x.scheme() == "http" || x.scheme() == "https"))
+    .map(|line| feed_sitemap_url(line.to_string(), None))
+    .collect();
+
+  if urls.is_empty() {
+    return Err("Invalid sitemap format: no URLs found in text sitemap".to_string());
+  }
+
+  Ok(ParsedSitemap {
+    urlset: Some(SitemapUrlset { url: urls }),
+    sitemapindex: None,
+  })
+}
+
+/// Parse any supported sitemap source: XML sitemaps, RSS/Atom feeds or
+/// plain-text URL lists.
+fn _parse_sitemap_content(
+  content: &str,
+  base_url: Option<&Url>,
+) -> std::result::Result<ParsedSitemap, String> {
+  if content.trim_start().starts_with('<') {
+    _parse_sitemap_xml(content, base_url)
+  } else {
+    _parse_sitemap_text(content)
   }
 }
 
-/// Parse XML sitemap content into structured data.
+/// Parse XML sitemap content into structured data. Relative feed links are
+/// resolved against `url` when given.
 #[napi]
-pub fn parse_sitemap_xml(xml_content: String) -> Result<ParsedSitemap> {
-  _parse_sitemap_xml(&xml_content).map_err(|e| {
+pub fn parse_sitemap_xml(xml_content: String, url: Option<String>) -> Result<ParsedSitemap> {
+  let base_url = url.as_deref().and_then(|x| Url::parse(x).ok());
+  _parse_sitemap_xml(&xml_content, base_url.as_ref()).map_err(|e| {
     Error::new(
       Status::GenericFailure,
       format!("Parse sitemap XML error: {e}"),
@@ -587,15 +1862,32 @@ pub fn parse_sitemap_xml(xml_content: String) -> Result<ParsedSitemap> {
   })
 }
 
-fn _process_sitemap(xml_content: &str) -> std::result::Result<SitemapProcessingResult, String> {
-  let parsed = _parse_sitemap_xml(xml_content)?;
+fn _process_sitemap(
+  xml_content: &str,
+  options: &ProcessSitemapOptions,
+) -> std::result::Result<SitemapProcessingResult, String> {
+  let base_url = match options.base_url.as_deref() {
+    Some(x) => Some(Url::parse(x).map_err(|_| format!("Invalid base_url: {x}"))?),
+    None => None,
+  };
+  let parsed = _parse_sitemap_content(xml_content, base_url.as_ref())?;
   let mut instructions = Vec::new();
   let mut total_count: u32 = 0;
 
+  let modified_after = match options.modified_after.as_deref() {
+    Some(x) => Some(parse_w3c_datetime(x).ok_or_else(|| format!("Invalid modified_after: {x}"))?),
+    None => None,
+  };
+  let is_modified = |lastmod: &Option<String>| match (modified_after, sitemap_lastmod(lastmod)) {
+    (Some(after), Some(lastmod)) => lastmod > after,
+    _ => true,
+  };
+
   if let Some(sitemapindex) = parsed.sitemapindex {
     let sitemap_urls: Vec<String> = sitemapindex
       .sitemap
       .iter()
+      .filter(|sitemap| is_modified(&sitemap.lastmod))
       .filter_map(|sitemap| {
         if !sitemap.loc.is_empty() {
           Some(sitemap.loc[0].trim().to_string())
@@ -616,7 +1908,7 @@ fn _process_sitemap(xml_content: &str) -> std::result::Result<SitemapProcessingR
     }
   } else if let Some(urlset) = parsed.urlset {
     let mut xml_sitemaps = Vec::new();
-    let mut valid_urls = Vec::new();
+    let mut valid_entries = Vec::new();
 
     for url_entry in urlset.url {
       if !url_entry.loc.is_empty() {
@@ -627,15 +1919,63 @@ fn _process_sitemap(xml_content: &str) -> std::result::Result<SitemapProcessingR
         } else if let Ok(parsed_url) = Url::parse(url) {
           let path_lower = parsed_url.path().to_lowercase();
           if !is_file(&path_lower) {
-            valid_urls.push(url.to_string());
+            valid_entries.push(url_entry);
           }
         }
       }
     }
 
-    if !xml_sitemaps.is_empty() {
-      let count = xml_sitemaps.len() as u32;
-      instructions.push(SitemapInstruction {
+    valid_entries.retain(|entry| {
+      is_modified(&entry.lastmod)
+        && options
+          .min_priority
+          .is_none_or(|min| sitemap_priority(entry.priority) >= min)
+        && options.changefreqs.as_ref().is_none_or(|changefreqs| {
+          entry
+            .changefreq
+            .as_ref()
+            .is_some_and(|x| changefreqs.iter().any(|c| c.eq_ignore_ascii_case(x.trim())))
+        })
+    });
+
+    match options.sort_by.as_deref() {
+      Some("lastmod") => {
+        valid_entries.sort_by_key(|entry| std::cmp::Reverse(sitemap_lastmod(&entry.lastmod)))
+      }
+      Some("priority") => valid_entries.sort_by(|a, b| {
+        sitemap_priority(b.priority)
+          .partial_cmp(&sitemap_priority(a.priority))
+          .unwrap_or(std::cmp::Ordering::Equal)
+      }),
+      Some(x) => return Err(format!("Invalid sort_by: {x}")),
+      None => {}
+    }
+
+    let include_alternates = options.include_alternates.unwrap_or(false);
+    let mut seen = HashSet::new();
+    let mut valid_urls: Vec<String> = Vec::new();
+    for entry in valid_entries {
+      // Duplicate locs are only collapsed when alternates are merged in;
+      // otherwise the urlset is passed through as before.
+      let url = entry.loc[0].trim().to_string();
+      if !include_alternates || seen.insert(url.clone()) {
+        valid_urls.push(url);
+      }
+
+      if include_alternates {
+        for alternate in entry.alternates.into_iter().flatten() {
+          let is_page =
+            Url::parse(&alternate.href).is_ok_and(|x| !is_file(&x.path().to_lowercase()));
+          if is_page && seen.insert(alternate.href.clone()) {
+            valid_urls.push(alternate.href);
+          }
+        }
+      }
+    }
+
+    if !xml_sitemaps.is_empty() {
+      let count = xml_sitemaps.len() as u32;
+      instructions.push(SitemapInstruction {
         action: "recurse".to_string(),
         urls: xml_sitemaps,
         count,
@@ -660,10 +2000,77 @@ fn _process_sitemap(xml_content: &str) -> std::result::Result<SitemapProcessingR
   })
 }
 
-/// Process sitemap XML and extract crawling instructions.
+fn _decode_sitemap_bytes(content: &[u8], max_size: u32) -> std::result::Result<String, String> {
+  let bytes = if content.starts_with(&[0x1f, 0x8b]) {
+    let mut inflated = Vec::new();
+    MultiGzDecoder::new(content)
+      .take(max_size as u64 + 1)
+      .read_to_end(&mut inflated)
+      .map_err(|e| format!("Gzip decompression error: {e}"))?;
+    if inflated.len() > max_size as usize {
+      return Err(format!(
+        "Decompressed sitemap exceeds maximum size of {max_size} bytes"
+      ));
+    }
+    inflated
+  } else {
+    content.to_vec()
+  };
+
+  let text = String::from_utf8_lossy(&bytes);
+  Ok(text.trim_start_matches('\u{feff}').to_string())
+}
+
+/// Process sitemap content (XML sitemap, RSS/Atom feed or plain-text URL
+/// list) and extract crawling instructions.
+#[napi]
+pub fn process_sitemap(
+  xml_content: String,
+  options: Option<ProcessSitemapOptions>,
+) -> Result<SitemapProcessingResult> {
+  _process_sitemap(&xml_content, &options.unwrap_or_default()).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Process sitemap error: {e}"),
+    )
+  })
+}
+
+/// Parse raw (optionally gzip-compressed) sitemap bytes into structured data.
 #[napi]
-pub fn process_sitemap(xml_content: String) -> Result<SitemapProcessingResult> {
-  _process_sitemap(&xml_content).map_err(|e| {
+pub fn parse_sitemap_bytes(
+  content: Buffer,
+  max_decompressed_size: Option<u32>,
+) -> Result<ParsedSitemap> {
+  _decode_sitemap_bytes(
+    &content,
+    max_decompressed_size.unwrap_or(MAX_SITEMAP_DECOMPRESSED_SIZE),
+  )
+  .and_then(|content| _parse_sitemap_content(&content, None))
+  .map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Parse sitemap bytes error: {e}"),
+    )
+  })
+}
+
+/// Process raw (optionally gzip-compressed) sitemap bytes and extract
+/// crawling instructions.
+#[napi]
+pub fn process_sitemap_bytes(
+  content: Buffer,
+  options: Option<ProcessSitemapOptions>,
+) -> Result<SitemapProcessingResult> {
+  let options = options.unwrap_or_default();
+  _decode_sitemap_bytes(
+    &content,
+    options
+      .max_decompressed_size
+      .unwrap_or(MAX_SITEMAP_DECOMPRESSED_SIZE),
+  )
+  .and_then(|xml_content| _process_sitemap(&xml_content, &options))
+  .map_err(|e| {
     Error::new(
       Status::GenericFailure,
       format!("Process sitemap error: {e}"),
@@ -675,6 +2082,45 @@ pub fn process_sitemap(xml_content: String) -> Result<SitemapProcessingResult> {
 mod tests {
   use super::*;
 
+  /// Call for `https://example.com` that respects robots.txt and has no other
+  /// rules; tests override what they exercise.
+  fn filter_links_call(links: &[&str]) -> FilterLinksCall {
+    FilterLinksCall {
+      links: links.iter().map(|x| x.to_string()).collect(),
+      options: LinkFilterOptions {
+        rules: url_rule_options(),
+        limit: Some(10),
+        max_depth: 10,
+        initial_url: "https://example.com".to_string(),
+        regex_on_full_url: false,
+        includes: vec![],
+        allow_backward_crawling: true,
+        include_extensions: None,
+        exclude_extensions: None,
+        include_file_types: None,
+        explain_denials: None,
+        deduplicate: None,
+      },
+    }
+  }
+
+  fn url_rule_options() -> UrlRuleOptions {
+    UrlRuleOptions {
+      base_url: "https://example.com".to_string(),
+      excludes: vec![],
+      include_globs: None,
+      exclude_globs: None,
+      depth_strategy: None,
+      ignore_robots_txt: false,
+      robots_txt: String::new(),
+      allow_external_content_links: false,
+      allow_subdomains: false,
+      user_agents: None,
+      blocked_domains: None,
+      allowed_domains: None,
+    }
+  }
+
   #[test]
   fn test_parse_sitemap_xml_urlset() {
     let xml_content = r#"<?xml version="1.0" encoding="UTF-8"?>
@@ -687,7 +2133,7 @@ mod tests {
   </url>
 </urlset>"#;
 
-    let result = _parse_sitemap_xml(xml_content).unwrap();
+    let result = _parse_sitemap_xml(xml_content, None).unwrap();
     assert!(result.urlset.is_some());
     let urlset = result.urlset.unwrap();
     assert_eq!(urlset.url.len(), 2);
@@ -707,7 +2153,7 @@ mod tests {
   </sitemap>
 </sitemapindex>"#;
 
-    let result = _parse_sitemap_xml(xml_content).unwrap();
+    let result = _parse_sitemap_xml(xml_content, None).unwrap();
     assert!(result.sitemapindex.is_some());
     let sitemapindex = result.sitemapindex.unwrap();
     assert_eq!(sitemapindex.sitemap.len(), 2);
@@ -730,7 +2176,7 @@ mod tests {
   </url>
 </invalid>"#;
 
-    let result = _parse_sitemap_xml(xml_content);
+    let result = _parse_sitemap_xml(xml_content, None);
     assert!(result.is_err());
     assert!(result.unwrap_err().contains("Invalid sitemap format"));
   }
@@ -744,7 +2190,7 @@ mod tests {
   </url>
 </urlset"#; // Missing closing >
 
-    let result = _parse_sitemap_xml(xml_content);
+    let result = _parse_sitemap_xml(xml_content, None);
     assert!(result.is_err());
   }
 
@@ -761,9 +2207,15 @@ mod tests {
   <url>
     <loc>https://example.com/image.png</loc>
   </url>
+  <url>
+    <loc>https://example.com/slides.PPTX</loc>
+  </url>
+  <url>
+    <loc>https://example.com/data.xlsx</loc>
+  </url>
 </urlset>"#;
 
-    let result = _process_sitemap(xml_content).unwrap();
+    let result = _process_sitemap(xml_content, &ProcessSitemapOptions::default()).unwrap();
     assert_eq!(result.instructions.len(), 2);
 
     let recurse_instruction = result
@@ -798,7 +2250,7 @@ mod tests {
   </sitemap>
 </sitemapindex>"#;
 
-    let result = _process_sitemap(xml_content).unwrap();
+    let result = _process_sitemap(xml_content, &ProcessSitemapOptions::default()).unwrap();
     assert_eq!(result.instructions.len(), 1);
     assert_eq!(result.instructions[0].action, "recurse");
     assert_eq!(result.instructions[0].urls.len(), 2);
@@ -812,28 +2264,436 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_parse_sitemap_xml_metadata() {
+    let xml_content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url>
+    <loc>https://example.com/page1</loc>
+    <lastmod>2024-05-01T10:00:00+02:00</lastmod>
+    <changefreq>daily</changefreq>
+    <priority>0.8</priority>
+  </url>
+  <url>
+    <loc>https://example.com/page2</loc>
+  </url>
+</urlset>"#;
+
+    let result = _parse_sitemap_xml(xml_content, None).unwrap();
+    let urlset = result.urlset.unwrap();
+    assert_eq!(
+      urlset.url[0].lastmod.as_deref(),
+      Some("2024-05-01T10:00:00+02:00")
+    );
+    assert_eq!(urlset.url[0].changefreq.as_deref(), Some("daily"));
+    assert_eq!(urlset.url[0].priority, Some(0.8));
+    assert!(urlset.url[1].lastmod.is_none());
+    assert!(urlset.url[1].changefreq.is_none());
+    assert!(urlset.url[1].priority.is_none());
+  }
+
+  #[test]
+  fn test_parse_w3c_datetime() {
+    assert_eq!(parse_w3c_datetime("1970-01-01"), Some(0));
+    assert_eq!(parse_w3c_datetime("2024"), parse_w3c_datetime("2024-01-01"));
+    assert_eq!(
+      parse_w3c_datetime("2024-05-01T10:00:00+02:00"),
+      parse_w3c_datetime("2024-05-01T08:00:00Z")
+    );
+    assert_eq!(
+      parse_w3c_datetime("2024-05-01T08:00:00.123Z"),
+      Some(1714550400)
+    );
+    assert_eq!(
+      parse_w3c_datetime("2024-05-01T10:00:00+0200"),
+      parse_w3c_datetime("2024-05-01T08:00:00Z")
+    );
+    assert_eq!(parse_w3c_datetime("2024-05-01T10:00:00+200"), None);
+    assert_eq!(parse_w3c_datetime("2024-05-01T10:00:00+2400"), None);
+    assert_eq!(parse_w3c_datetime("not a date"), None);
+    assert_eq!(parse_w3c_datetime("2024-13-01"), None);
+    assert!(parse_w3c_datetime("2024-02-29").is_some());
+    assert!(parse_w3c_datetime("2000-02-29").is_some());
+    assert_eq!(parse_w3c_datetime("2023-02-29"), None);
+    assert_eq!(parse_w3c_datetime("1900-02-29"), None);
+    assert_eq!(parse_w3c_datetime("2024-02-31"), None);
+    assert_eq!(parse_w3c_datetime("2024-04-31"), None);
+  }
+
+  #[test]
+  fn test_process_sitemap_filter_and_sort() {
+    let xml_content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url>
+    <loc>https://example.com/old</loc>
+    <lastmod>2023-01-01</lastmod>
+    <priority>1.0</priority>
+  </url>
+  <url>
+    <loc>https://example.com/new</loc>
+    <lastmod>2024-06-01</lastmod>
+    <priority>0.3</priority>
+  </url>
+  <url>
+    <loc>https://example.com/newer</loc>
+    <lastmod>2024-07-01T12:00:00Z</lastmod>
+    <priority>0.9</priority>
+  </url>
+  <url>
+    <loc>https://example.com/unknown</loc>
+  </url>
+</urlset>"#;
+
+    let result = _process_sitemap(
+      xml_content,
+      &ProcessSitemapOptions {
+        modified_after: Some("2024-01-01".to_string()),
+        sort_by: Some("lastmod".to_string()),
+        ..Default::default()
+      },
+    )
+    .unwrap();
+    assert_eq!(
+      result.instructions[0].urls,
+      vec![
+        "https://example.com/newer",
+        "https://example.com/new",
+        "https://example.com/unknown",
+      ]
+    );
+
+    let result = _process_sitemap(
+      xml_content,
+      &ProcessSitemapOptions {
+        min_priority: Some(0.5),
+        sort_by: Some("priority".to_string()),
+        ..Default::default()
+      },
+    )
+    .unwrap();
+    assert_eq!(
+      result.instructions[0].urls,
+      vec![
+        "https://example.com/old",
+        "https://example.com/newer",
+        "https://example.com/unknown",
+      ]
+    );
+
+    let result = _process_sitemap(
+      xml_content,
+      &ProcessSitemapOptions {
+        modified_after: Some("yesterday".to_string()),
+        ..Default::default()
+      },
+    );
+    assert!(result.is_err());
+  }
+
+  #[test]
+  fn test_parse_sitemap_xml_extensions() {
+    let xml_content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9"
+  xmlns:image="http://www.google.com/schemas/sitemap-image/1.1"
+  xmlns:video="http://www.google.com/schemas/sitemap-video/1.1"
+  xmlns:news="http://www.google.com/schemas/sitemap-news/0.9"
+  xmlns:xhtml="http://www.w3.org/1999/xhtml">
+  <url>
+    <loc>https://example.com/en/page</loc>
+    <xhtml:link rel="alternate" hreflang="de" href="https://example.com/de/page"/>
+    <xhtml:link rel="alternate" hreflang="en" href="https://example.com/en/page"/>
+    <image:image>
+      <image:loc>https://example.com/photo.jpg</image:loc>
+      <image:caption>A photo</image:caption>
+    </image:image>
+    <video:video>
+      <video:thumbnail_loc>https://example.com/thumb.jpg</video:thumbnail_loc>
+      <video:title>A video</video:title>
+      <video:content_loc>https://example.com/video.mp4</video:content_loc>
+      <video:duration>120</video:duration>
+      <video:family_friendly>no</video:family_friendly>
+      <video:tag>one</video:tag>
+      <video:tag>two</video:tag>
+    </video:video>
+    <news:news>
+      <news:publication>
+        <news:name>Example Times</news:name>
+        <news:language>en</news:language>
+      </news:publication>
+      <news:publication_date>2024-05-01</news:publication_date>
+      <news:title>Headline</news:title>
+    </news:news>
+  </url>
+</urlset>"#;
+
+    let result = _parse_sitemap_xml(xml_content, None).unwrap();
+    let url = &result.urlset.unwrap().url[0];
+
+    let images = url.images.as_ref().unwrap();
+    assert_eq!(images.len(), 1);
+    assert_eq!(images[0].loc, "https://example.com/photo.jpg");
+    assert_eq!(images[0].caption.as_deref(), Some("A photo"));
+
+    let videos = url.videos.as_ref().unwrap();
+    assert_eq!(videos[0].title.as_deref(), Some("A video"));
+    assert_eq!(videos[0].duration, Some(120));
+    assert_eq!(videos[0].family_friendly, Some(false));
+    assert_eq!(
+      videos[0].tags,
+      Some(vec!["one".to_string(), "two".to_string()])
+    );
+
+    let news = url.news.as_ref().unwrap();
+    assert_eq!(news.publication_name.as_deref(), Some("Example Times"));
+    assert_eq!(news.publication_language.as_deref(), Some("en"));
+    assert_eq!(news.title.as_deref(), Some("Headline"));
+
+    let alternates = url.alternates.as_ref().unwrap();
+    assert_eq!(alternates.len(), 2);
+    assert_eq!(
+      alternates[0],
+      SitemapAlternate {
+        hreflang: "de".to_string(),
+        href: "https://example.com/de/page".to_string(),
+      }
+    );
+  }
+
+  #[test]
+  fn test_process_sitemap_include_alternates() {
+    let xml_content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9"
+  xmlns:xhtml="http://www.w3.org/1999/xhtml">
+  <url>
+    <loc>https://example.com/en/page</loc>
+    <xhtml:link rel="alternate" hreflang="de" href="https://example.com/de/page"/>
+    <xhtml:link rel="alternate" hreflang="en" href="https://example.com/en/page"/>
+  </url>
+</urlset>"#;
+
+    let result = _process_sitemap(xml_content, &ProcessSitemapOptions::default()).unwrap();
+    assert_eq!(
+      result.instructions[0].urls,
+      vec!["https://example.com/en/page"]
+    );
+
+    let result = _process_sitemap(
+      xml_content,
+      &ProcessSitemapOptions {
+        include_alternates: Some(true),
+        ..Default::default()
+      },
+    )
+    .unwrap();
+    assert_eq!(
+      result.instructions[0].urls,
+      vec!["https://example.com/en/page", "https://example.com/de/page"]
+    );
+    assert_eq!(result.total_count, 2);
+  }
+
+  #[test]
+  fn test_decode_sitemap_bytes_gzip() {
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+
+    let xml_content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url>
+    <loc>https://example.com/page1</loc>
+  </url>
+</urlset>"#;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(xml_content.as_bytes()).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let decoded = _decode_sitemap_bytes(&compressed, MAX_SITEMAP_DECOMPRESSED_SIZE).unwrap();
+    assert_eq!(decoded, xml_content);
+
+    let plain =
+      _decode_sitemap_bytes(xml_content.as_bytes(), MAX_SITEMAP_DECOMPRESSED_SIZE).unwrap();
+    assert_eq!(plain, xml_content);
+
+    let result = _decode_sitemap_bytes(&compressed, 16);
+    assert!(result.is_err());
+    assert!(result.unwrap_err().contains("exceeds maximum size"));
+  }
+
+  #[test]
+  fn test_parse_sitemap_bytes_gzip_text() {
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+      .write_all(b"https://example.com/page1\nhttps://example.com/page2\n")
+      .unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let urlset = parse_sitemap_bytes(Buffer::from(compressed), None)
+      .unwrap()
+      .urlset
+      .unwrap();
+    let urls: Vec<&str> = urlset.url.iter().map(|x| x.loc[0].as_str()).collect();
+    assert_eq!(
+      urls,
+      vec!["https://example.com/page1", "https://example.com/page2"]
+    );
+  }
+
+  #[test]
+  fn test_decode_sitemap_bytes_invalid_gzip() {
+    let result = _decode_sitemap_bytes(&[0x1f, 0x8b, 0x00, 0x01], MAX_SITEMAP_DECOMPRESSED_SIZE);
+    assert!(result.is_err());
+  }
+
+  #[test]
+  fn test_process_sitemap_text() {
+    let content = "https://example.com/page1\n\n  https://example.com/page2  \n# comment\nnot a url\nhttps://example.com/sitemap2.xml\n";
+
+    let result = _process_sitemap(content, &ProcessSitemapOptions::default()).unwrap();
+    let recurse = result
+      .instructions
+      .iter()
+      .find(|i| i.action == "recurse")
+      .unwrap();
+    assert_eq!(recurse.urls, vec!["https://example.com/sitemap2.xml"]);
+    let process = result
+      .instructions
+      .iter()
+      .find(|i| i.action == "process")
+      .unwrap();
+    assert_eq!(
+      process.urls,
+      vec!["https://example.com/page1", "https://example.com/page2"]
+    );
+
+    assert!(_process_sitemap("nothing here", &ProcessSitemapOptions::default()).is_err());
+  }
+
+  #[test]
+  fn test_process_sitemap_rss() {
+    let xml_content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0">
+  <channel>
+    <title>Example</title>
+    <link>https://example.com/</link>
+    <item>
+      <title>Old post</title>
+      <link>https://example.com/old</link>
+      <pubDate>Tue, 10 Jun 2003 04:00:00 GMT</pubDate>
+    </item>
+    <item>
+      <title>New post</title>
+      <guid isPermaLink="true">https://example.com/new</guid>
+      <pubDate>Wed, 01 May 2024 10:00:00 +0200</pubDate>
+    </item>
+  </channel>
+</rss>"#;
+
+    let parsed = _parse_sitemap_xml(xml_content, None).unwrap();
+    let urlset = parsed.urlset.unwrap();
+    assert_eq!(urlset.url.len(), 2);
+    assert_eq!(
+      urlset.url[1].lastmod.as_deref(),
+      Some("2024-05-01T10:00:00+02:00")
+    );
+
+    let result = _process_sitemap(
+      xml_content,
+      &ProcessSitemapOptions {
+        modified_after: Some("2024-01-01".to_string()),
+        ..Default::default()
+      },
+    )
+    .unwrap();
+    assert_eq!(result.instructions.len(), 1);
+    assert_eq!(result.instructions[0].action, "process");
+    assert_eq!(result.instructions[0].urls, vec!["https://example.com/new"]);
+  }
+
+  #[test]
+  fn test_process_sitemap_atom() {
+    let xml_content = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Example</title>
+  <link href="https://example.com/"/>
+  <entry>
+    <title>Post</title>
+    <link rel="self" href="https://example.com/feed/post"/>
+    <link rel="alternate" type="text/html" href="https://example.com/post"/>
+    <updated>2024-05-01T08:00:00Z</updated>
+  </entry>
+  <entry>
+    <title>Other</title>
+    <link href="https://example.com/other"/>
+  </entry>
+</feed>"#;
+
+    let result = _process_sitemap(xml_content, &ProcessSitemapOptions::default()).unwrap();
+    assert_eq!(result.instructions.len(), 1);
+    assert_eq!(result.instructions[0].action, "process");
+    assert_eq!(
+      result.instructions[0].urls,
+      vec!["https://example.com/post", "https://example.com/other"]
+    );
+  }
+
+  #[test]
+  fn test_process_sitemap_atom_relative_links() {
+    let xml_content = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <entry>
+    <link href="/post"/>
+  </entry>
+  <entry xml:base="https://example.com/blog/">
+    <link href="other"/>
+  </entry>
+</feed>"#;
+
+    let result = _process_sitemap(
+      xml_content,
+      &ProcessSitemapOptions {
+        base_url: Some("https://example.com/feed.xml".to_string()),
+        ..Default::default()
+      },
+    )
+    .unwrap();
+    assert_eq!(
+      result.instructions[0].urls,
+      vec!["https://example.com/post", "https://example.com/blog/other"]
+    );
+
+    let result = _process_sitemap(xml_content, &ProcessSitemapOptions::default()).unwrap();
+    assert_eq!(
+      result.instructions[0].urls,
+      vec!["https://example.com/blog/other"]
+    );
+  }
+
+  #[test]
+  fn test_rfc822_to_w3c() {
+    assert_eq!(
+      rfc822_to_w3c("Tue, 10 Jun 2003 04:00:00 GMT").as_deref(),
+      Some("2003-06-10T04:00:00+00:00")
+    );
+    assert_eq!(
+      rfc822_to_w3c("1 Jan 99 12:30 EST").as_deref(),
+      Some("1999-01-01T12:30:00-05:00")
+    );
+    assert_eq!(rfc822_to_w3c("yesterday"), None);
+  }
+
   #[test]
   fn test_filter_links_normal_robots_txt() {
-    let data = FilterLinksCall {
-      links: vec![
-        "https://example.com/allowed".to_string(),
-        "https://example.com/disallowed".to_string(),
-      ],
-      limit: Some(10),
-      includes: vec![],
-      excludes: vec![],
-      ignore_robots_txt: false,
-      robots_txt: "User-agent: *\nDisallow: /disallowed".to_string(),
-      max_depth: 10,
-      base_url: "https://example.com".to_string(),
-      initial_url: "https://example.com".to_string(),
-      regex_on_full_url: false,
-      allow_backward_crawling: true,
-      allow_external_content_links: false,
-      allow_subdomains: false,
-    };
+    let mut data = filter_links_call(&[
+      "https://example.com/allowed",
+      "https://example.com/disallowed",
+    ]);
+    data.options.rules.robots_txt = "User-agent: *\nDisallow: /disallowed".to_string();
 
-    let result = _filter_links(data).unwrap();
+    let result = _filter_links(data, None).unwrap();
     assert_eq!(result.links.len(), 1);
     assert_eq!(result.links[0], "https://example.com/allowed");
     assert!(result
@@ -850,24 +2710,11 @@ mod tests {
 
   #[test]
   fn test_filter_links_malformed_robots_txt() {
-    let data = FilterLinksCall {
-      links: vec!["https://example.com/test".to_string()],
-      limit: Some(10),
-      includes: vec![],
-      excludes: vec![],
-      ignore_robots_txt: false,
-      robots_txt: "Invalid robots.txt content with \x00 null bytes and malformed syntax"
-        .to_string(),
-      max_depth: 10,
-      base_url: "https://example.com".to_string(),
-      initial_url: "https://example.com".to_string(),
-      regex_on_full_url: false,
-      allow_backward_crawling: true,
-      allow_external_content_links: false,
-      allow_subdomains: false,
-    };
+    let mut data = filter_links_call(&["https://example.com/test"]);
+    data.options.rules.robots_txt =
+      "Invalid robots.txt content with \x00 null bytes and malformed syntax".to_string();
 
-    let result = _filter_links(data);
+    let result = _filter_links(data, None);
     assert!(result.is_ok());
     let result = result.unwrap();
     assert_eq!(result.links.len(), 1);
@@ -880,23 +2727,10 @@ mod tests {
     non_utf8_bytes.extend_from_slice(b"User-agent: *\nDisallow: /blocked");
     let non_utf8_string = String::from_utf8_lossy(&non_utf8_bytes).to_string();
 
-    let data = FilterLinksCall {
-      links: vec!["https://example.com/allowed".to_string()],
-      limit: Some(10),
-      includes: vec![],
-      excludes: vec![],
-      ignore_robots_txt: false,
-      robots_txt: non_utf8_string,
-      max_depth: 10,
-      base_url: "https://example.com".to_string(),
-      initial_url: "https://example.com".to_string(),
-      regex_on_full_url: false,
-      allow_backward_crawling: true,
-      allow_external_content_links: false,
-      allow_subdomains: false,
-    };
+    let mut data = filter_links_call(&["https://example.com/allowed"]);
+    data.options.rules.robots_txt = non_utf8_string;
 
-    let result = _filter_links(data);
+    let result = _filter_links(data, None);
     assert!(result.is_ok());
     let result = result.unwrap();
     assert_eq!(result.links.len(), 1);
@@ -907,29 +2741,514 @@ mod tests {
   fn test_filter_links_char_boundary_issue() {
     let problematic_content = "User-agent: *\nDisallow: /\u{a0}test";
 
-    let data = FilterLinksCall {
-      links: vec!["https://example.com/test".to_string()],
-      limit: Some(10),
-      includes: vec![],
-      excludes: vec![],
-      ignore_robots_txt: false,
-      robots_txt: problematic_content.to_string(),
-      max_depth: 10,
-      base_url: "https://example.com".to_string(),
-      initial_url: "https://example.com".to_string(),
-      regex_on_full_url: false,
-      allow_backward_crawling: true,
-      allow_external_content_links: false,
-      allow_subdomains: false,
-    };
+    let mut data = filter_links_call(&["https://example.com/test"]);
+    data.options.rules.robots_txt = problematic_content.to_string();
 
-    let result = _filter_links(data);
+    let result = _filter_links(data, None);
     assert!(result.is_ok());
     let result = result.unwrap();
     assert_eq!(result.links.len(), 1);
     assert_eq!(result.links[0], "https://example.com/test");
   }
 
+  #[test]
+  fn test_link_filter_reuse() {
+    let mut options = filter_links_call(&[]).options;
+    options.max_depth = 2;
+    options.initial_url = "https://example.com/docs".to_string();
+    options.rules.excludes = vec!["/private".to_string()];
+    options.allow_backward_crawling = false;
+    options.rules.robots_txt = "User-agent: *\nDisallow: /docs/blocked".to_string();
+    let filter = LinkFilter::compile(options).unwrap();
+
+    let result = filter.filter_links(vec![
+      "https://example.com/docs/page".to_string(),
+      "/docs/blocked".to_string(),
+      "/blog".to_string(),
+    ]);
+    assert_eq!(result.links, vec!["https://example.com/docs/page"]);
+    assert_eq!(result.denial_reasons["/docs/blocked"], ROBOTS_TXT);
+    assert_eq!(result.denial_reasons["/blog"], BACKWARD_CRAWLING);
+
+    let result = filter.filter_links(vec!["/docs/a/b/c".to_string(), "/docs/private".to_string()]);
+    assert!(result.links.is_empty());
+    assert_eq!(result.denial_reasons["/docs/a/b/c"], DEPTH_LIMIT);
+    assert_eq!(result.denial_reasons["/docs/private"], EXCLUDE_PATTERN);
+
+    let result = filter.filter_url("page".to_string(), "https://example.com/docs/".to_string());
+    assert!(result.allowed);
+    assert_eq!(result.url.as_deref(), Some("https://example.com/docs/page"));
+
+    let result = filter.filter_url(
+      "https://twitter.com/example".to_string(),
+      "https://example.com/docs/".to_string(),
+    );
+    assert!(!result.allowed);
+    assert_eq!(result.denial_reason.as_deref(), Some(SOCIAL_MEDIA));
+  }
+
+  #[test]
+  fn test_filter_links_custom_user_agents() {
+    let robots_txt = "User-agent: *\nDisallow: /all\n\nUser-agent: MyBot\nDisallow: /mybot\n\nUser-agent: FirecrawlAgent\nDisallow: /firecrawl";
+    let call = |user_agents: Option<Vec<String>>| {
+      let mut data = filter_links_call(&[
+        "https://example.com/all",
+        "https://example.com/mybot",
+        "https://example.com/firecrawl",
+      ]);
+      data.options.rules.robots_txt = robots_txt.to_string();
+      data.options.rules.user_agents = user_agents;
+      data
+    };
+
+    let result = _filter_links(call(None), None).unwrap();
+    assert_eq!(
+      result.links,
+      vec!["https://example.com/all", "https://example.com/mybot"]
+    );
+
+    let result = _filter_links(
+      call(Some(vec![
+        "OtherBot".to_string(),
+        "MyBot".to_string(),
+        "FirecrawlAgent".to_string(),
+      ])),
+      None,
+    )
+    .unwrap();
+    assert_eq!(
+      result.links,
+      vec!["https://example.com/all", "https://example.com/firecrawl"]
+    );
+
+    let result = _filter_links(call(Some(vec!["OtherBot".to_string()])), None).unwrap();
+    assert_eq!(
+      result.links,
+      vec!["https://example.com/mybot", "https://example.com/firecrawl"]
+    );
+  }
+
+  #[test]
+  fn test_filter_links_blocked_domains() {
+    let call = |blocked_domains, allowed_domains| {
+      let mut data = filter_links_call(&[
+        "https://github.com/org/repo",
+        "https://docs.github.com/en/get-started",
+        "https://notfacebook.company.com/page",
+        "https://www.facebook.com/page",
+        "https://example.org/page",
+        "mailto:hello@example.com",
+      ]);
+      data.options.rules.ignore_robots_txt = true;
+      data.options.rules.allow_external_content_links = true;
+      data.options.rules.blocked_domains = blocked_domains;
+      data.options.rules.allowed_domains = allowed_domains;
+      data
+    };
+
+    let result = _filter_links(call(None, None), None).unwrap();
+    assert_eq!(
+      result.links,
+      vec![
+        "https://notfacebook.company.com/page",
+        "https://example.org/page"
+      ]
+    );
+    assert_eq!(
+      result.denial_reasons["https://docs.github.com/en/get-started"],
+      SOCIAL_MEDIA
+    );
+    assert_eq!(
+      result.denial_reasons["mailto:hello@example.com"],
+      SOCIAL_MEDIA
+    );
+
+    let result = _filter_links(call(None, Some(vec!["github.com".to_string()])), None).unwrap();
+    assert!(result
+      .links
+      .contains(&"https://docs.github.com/en/get-started".to_string()));
+    assert!(result
+      .links
+      .contains(&"https://github.com/org/repo".to_string()));
+    assert_eq!(
+      result.denial_reasons["https://www.facebook.com/page"],
+      SOCIAL_MEDIA
+    );
+
+    let result =
+      _filter_links(call(Some(vec!["www.example.org".to_string()]), None), None).unwrap();
+    assert_eq!(result.links.len(), 4);
+    assert_eq!(
+      result.denial_reasons["https://example.org/page"],
+      SOCIAL_MEDIA
+    );
+  }
+
+  #[test]
+  fn test_glob_to_regex() {
+    let matches = |glob: &str, path: &str| glob_to_regex(glob).unwrap().is_match(path);
+
+    assert!(matches("/blog/**", "/blog/a/b"));
+    assert!(matches("/blog/**", "/blog"));
+    assert!(!matches("/blog/**", "/blogs/a"));
+    assert!(matches("/blog/*", "/blog/post"));
+    assert!(matches("/docs/**/intro", "/docs/intro"));
+    assert!(matches("/docs/**/intro", "/docs/v1/guide/intro"));
+    assert!(matches("*.pdf", "/files/report.pdf"));
+    assert!(!matches("*.pdf", "/files/report.pdfx"));
+    assert!(matches("/post-?", "/post-1"));
+    assert!(matches("/v[0-9]/api", "/v2/api/users"));
+    assert!(!matches("/v[!0-9]/api", "/v2/api"));
+    assert!(matches("/a+b", "/a+b"));
+    assert!(glob_to_regex("/blog/a**").is_err());
+    assert!(glob_to_regex("/v[0-9/api").is_err());
+  }
+
+  #[test]
+  fn test_filter_links_globs() {
+    let call = |includes: Vec<&str>, include_globs: Vec<&str>, exclude_globs: Vec<&str>| {
+      let mut data = filter_links_call(&[
+        "https://example.com/blog/post",
+        "https://example.com/blog/drafts/wip",
+        "https://example.com/about",
+      ]);
+      data.options.includes = includes.into_iter().map(String::from).collect();
+      data.options.rules.include_globs =
+        Some(include_globs.into_iter().map(String::from).collect());
+      data.options.rules.exclude_globs =
+        Some(exclude_globs.into_iter().map(String::from).collect());
+      data.options.rules.ignore_robots_txt = true;
+      data
+    };
+
+    let result = _filter_links(
+      call(vec![], vec!["/blog/**", "!/blog/drafts/*"], vec![]),
+      None,
+    )
+    .unwrap();
+    assert_eq!(result.links, vec!["https://example.com/blog/post"]);
+    assert_eq!(
+      result.denial_reasons["https://example.com/blog/drafts/wip"],
+      INCLUDE_PATTERN
+    );
+    assert!(result.invalid_patterns.is_empty());
+
+    let result = _filter_links(call(vec![], vec![], vec!["/blog/drafts"]), None).unwrap();
+    assert_eq!(
+      result.links,
+      vec!["https://example.com/blog/post", "https://example.com/about"]
+    );
+    assert_eq!(
+      result.denial_reasons["https://example.com/blog/drafts/wip"],
+      EXCLUDE_PATTERN
+    );
+
+    let result = _filter_links(call(vec![], vec!["!/about"], vec![]), None).unwrap();
+    assert_eq!(result.links.len(), 2);
+
+    // A leading negation in an exclude list is rejected rather than
+    // excluding everything else
+    let result = _filter_links(call(vec![], vec![], vec!["!/about"]), None).unwrap();
+    assert_eq!(result.links.len(), 3);
+    assert!(result.invalid_patterns.contains_key("!/about"));
+
+    let mut data = call(vec![], vec![], vec!["!/about", "/blog", "!/blog/drafts"]);
+    data.options.explain_denials = Some(true);
+    let result = _filter_links(data, None).unwrap();
+    assert_eq!(
+      result.links,
+      vec![
+        "https://example.com/blog/drafts/wip",
+        "https://example.com/about"
+      ]
+    );
+    let details = result.denial_details.unwrap();
+    assert_eq!(
+      details["https://example.com/blog/post"].reason,
+      EXCLUDE_PATTERN
+    );
+    assert_eq!(
+      details["https://example.com/blog/post"].pattern.as_deref(),
+      Some("/blog")
+    );
+
+    let result =
+      _filter_links(call(vec!["blog(", "^/blog"], vec!["/a/**b"], vec![]), None).unwrap();
+    assert_eq!(
+      result.links,
+      vec![
+        "https://example.com/blog/post",
+        "https://example.com/blog/drafts/wip"
+      ]
+    );
+    assert!(result.invalid_patterns.contains_key("blog("));
+    assert!(result.invalid_patterns.contains_key("/a/**b"));
+  }
+
+  #[test]
+  fn test_filter_url_globs() {
+    let call = |href: &str| FilterUrlCall {
+      href: href.to_string(),
+      url: "https://example.com/".to_string(),
+      rules: UrlRuleOptions {
+        excludes: vec!["blog(".to_string()],
+        include_globs: Some(vec!["/blog/**".to_string(), "/about".to_string()]),
+        exclude_globs: Some(vec!["/blog/drafts/**".to_string()]),
+        ignore_robots_txt: true,
+        ..url_rule_options()
+      },
+    };
+
+    let result = _filter_url(call("/blog/post")).unwrap();
+    assert!(result.allowed);
+    assert!(result.invalid_patterns.contains_key("blog("));
+
+    let result = _filter_url(call("/blog/drafts/wip")).unwrap();
+    assert_eq!(result.denial_reason.as_deref(), Some(EXCLUDE_PATTERN));
+
+    let result = _filter_url(call("/pricing")).unwrap();
+    assert_eq!(result.denial_reason.as_deref(), Some(INCLUDE_PATTERN));
+
+    assert!(_filter_url(call("/blog/#/post")).unwrap().allowed);
+    let mut data = call("/blog/#/post");
+    data.rules.depth_strategy = Some(DepthStrategy {
+      hash_routes: Some(false),
+      ..Default::default()
+    });
+    let result = _filter_url(data).unwrap();
+    assert_eq!(result.denial_reason.as_deref(), Some(SECTION_LINK));
+  }
+
+  #[test]
+  fn test_filter_links_denial_details() {
+    let robots_txt = "User-agent: *\nDisallow: /private\n".to_string();
+    let call = |explain_denials| {
+      let mut data = filter_links_call(&[
+        "/a/b/c/d",
+        "/private/page",
+        "/admin/users",
+        "/image.png",
+        "https://www.facebook.com/page",
+        "/ok",
+        "/ok/",
+      ]);
+      data.options.rules.excludes = vec!["^/admin".to_string()];
+      data.options.rules.robots_txt = robots_txt.clone();
+      data.options.max_depth = 3;
+      data.options.explain_denials = explain_denials;
+      data.options.deduplicate = Some(true);
+      data
+    };
+
+    assert!(_filter_links(call(None), None)
+      .unwrap()
+      .denial_details
+      .is_none());
+
+    let result = _filter_links(call(Some(true)), None).unwrap();
+    assert_eq!(result.links, vec!["https://example.com/ok"]);
+    let details = result.denial_details.unwrap();
+    assert_eq!(details.len(), result.denial_reasons.len());
+
+    let depth = &details["/a/b/c/d"];
+    assert_eq!(depth.reason, DEPTH_LIMIT);
+    assert_eq!(depth.depth, Some(4));
+    assert_eq!(depth.max_depth, Some(3));
+    assert_eq!(
+      depth.resolved_url.as_deref(),
+      Some("https://example.com/a/b/c/d")
+    );
+
+    let robots = &details["/private/page"];
+    assert_eq!(robots.reason, ROBOTS_TXT);
+    let rule = robots.robots_txt_rule.as_ref().unwrap();
+    assert_eq!(
+      (rule.allow, rule.pattern.as_str(), rule.line),
+      (false, "/private", 2)
+    );
+    assert_eq!(robots.robots_user_agent.as_deref(), Some("FireCrawlAgent"));
+
+    assert_eq!(details["/admin/users"].pattern.as_deref(), Some("^/admin"));
+    assert_eq!(details["/image.png"].extension.as_deref(), Some(".png"));
+    assert_eq!(
+      details["https://www.facebook.com/page"].domain.as_deref(),
+      Some("facebook.com")
+    );
+    assert_eq!(details["/ok/"].reason, DUPLICATE);
+    assert_eq!(
+      details["/ok/"].duplicate_of.as_deref(),
+      Some("https://example.com/ok")
+    );
+  }
+
+  #[test]
+  fn test_filter_links_depth_strategy() {
+    let call = |depth_strategy| {
+      let mut data = filter_links_call(&[
+        "/#/app/settings/profile",
+        "/list?page=2",
+        "/list?page=2&sort=asc",
+        "/list?sort=desc&page=2&sid=1",
+        "/about#team",
+      ]);
+      data.options.rules.ignore_robots_txt = true;
+      data.options.max_depth = 1;
+      data.options.rules.depth_strategy = depth_strategy;
+      data.options.explain_denials = Some(true);
+      data.options.deduplicate = Some(true);
+      data
+    };
+
+    let result = _filter_links(call(None), None).unwrap();
+    assert_eq!(
+      result.links,
+      vec![
+        "https://example.com/#/app/settings/profile",
+        "https://example.com/list?page=2",
+        "https://example.com/list?page=2&sort=asc",
+        "https://example.com/list?page=2&sid=1&sort=desc"
+      ]
+    );
+    assert_eq!(result.denial_reasons["/about#team"], SECTION_LINK);
+
+    let result = _filter_links(
+      call(Some(DepthStrategy {
+        hash_routes: Some(true),
+        query_params_as_levels: Some(vec!["page".to_string()]),
+        ignore_query_params: Some(vec!["sort".to_string(), "sid".to_string()]),
+      })),
+      None,
+    )
+    .unwrap();
+    assert_eq!(result.links, Vec::<String>::new());
+    let details = result.denial_details.unwrap();
+    assert_eq!(details["/#/app/settings/profile"].depth, Some(3));
+    assert_eq!(details["/list?page=2"].depth, Some(2));
+
+    let result = _filter_links(
+      call(Some(DepthStrategy {
+        hash_routes: None,
+        query_params_as_levels: None,
+        ignore_query_params: Some(vec!["sort".to_string(), "sid".to_string()]),
+      })),
+      None,
+    )
+    .unwrap();
+    assert_eq!(
+      result.links,
+      vec![
+        "https://example.com/#/app/settings/profile",
+        "https://example.com/list?page=2"
+      ]
+    );
+    assert_eq!(result.denial_reasons["/list?page=2&sort=asc"], DUPLICATE);
+
+    let result = _filter_links(
+      call(Some(DepthStrategy {
+        hash_routes: Some(false),
+        query_params_as_levels: None,
+        ignore_query_params: None,
+      })),
+      None,
+    )
+    .unwrap();
+    assert_eq!(
+      result.denial_reasons["/#/app/settings/profile"],
+      SECTION_LINK
+    );
+  }
+
+  #[test]
+  fn test_filter_unseen_links() {
+    let call = |links: Vec<&str>| {
+      let mut data = filter_links_call(&[]);
+      data.links = links.into_iter().map(String::from).collect();
+      data.options.rules.ignore_robots_txt = true;
+      data
+    };
+    let mut seen = SeenSet::default();
+
+    let result = _filter_links(call(vec!["/a", "/b", "/a/"]), Some(&mut seen)).unwrap();
+    assert_eq!(
+      result.links,
+      vec!["https://example.com/a", "https://example.com/b"]
+    );
+    assert_eq!(result.denial_reasons["/a/"], DUPLICATE);
+
+    let result = _filter_links(call(vec!["/b?utm_source=x", "/c"]), Some(&mut seen)).unwrap();
+    assert_eq!(result.links, vec!["https://example.com/c"]);
+    assert_eq!(result.denial_reasons["/b?utm_source=x"], DUPLICATE);
+    assert_eq!(seen.size(), 3.0);
+  }
+
+  #[test]
+  fn test_normalize_url() {
+    let normalize = |x: &str| normalize_url(x.to_string(), None).unwrap();
+
+    assert_eq!(
+      normalize("HTTPS://Example.COM:443/Docs/"),
+      "https://example.com/Docs"
+    );
+    assert_eq!(normalize("http://example.com:80/"), "http://example.com/");
+    assert_eq!(
+      normalize("https://example.com/a/index.html"),
+      "https://example.com/a"
+    );
+    assert_eq!(
+      normalize("https://example.com/index.php"),
+      "https://example.com/"
+    );
+    assert_eq!(
+      normalize("https://example.com/%7euser/a%2fb"),
+      "https://example.com/~user/a%2Fb"
+    );
+    assert_eq!(
+      normalize("https://example.com/p?b=2&utm_source=x&a=1&fbclid=abc#top"),
+      "https://example.com/p?a=1&b=2"
+    );
+    assert_eq!(
+      normalize("https://example.com/p?utm_medium=email"),
+      "https://example.com/p"
+    );
+    assert_eq!(
+      normalize("https://example.com/#/app/page"),
+      "https://example.com/#/app/page"
+    );
+    assert!(normalize_url("not a url".to_string(), None).is_err());
+  }
+
+  #[test]
+  fn test_filter_links_deduplicate() {
+    let call = |deduplicate| {
+      let mut data = filter_links_call(&[
+        "https://example.com/page",
+        "https://example.com/page/",
+        "/page?utm_source=newsletter",
+        "https://example.com/other/index.html",
+      ]);
+      data.options.rules.ignore_robots_txt = true;
+      data.options.deduplicate = deduplicate;
+      data
+    };
+
+    let result = _filter_links(call(None), None).unwrap();
+    assert_eq!(result.links.len(), 4);
+
+    let result = _filter_links(call(Some(true)), None).unwrap();
+    assert_eq!(
+      result.links,
+      vec!["https://example.com/page", "https://example.com/other"]
+    );
+    assert_eq!(
+      result.denial_reasons["https://example.com/page/"],
+      DUPLICATE
+    );
+    assert_eq!(
+      result.denial_reasons["/page?utm_source=newsletter"],
+      DUPLICATE
+    );
+  }
+
   #[test]
   fn test_is_file() {
     assert!(is_file("test.png"));
@@ -937,5 +3256,105 @@ mod tests {
     assert!(is_file("style.css"));
     assert!(!is_file("page"));
     assert!(!is_file("directory/"));
+    assert!(!is_file("report.pdf"));
+    assert!(!is_file("data.csv"));
+  }
+
+  #[test]
+  fn test_filter_links_file_types() {
+    let call = |include_extensions, exclude_extensions, include_file_types| {
+      let mut data = filter_links_call(&[
+        "https://example.com/report.pdf",
+        "https://example.com/slides.pptx",
+        "https://example.com/data.csv",
+        "https://example.com/photo.jpg",
+        "https://example.com/style.css",
+        "https://example.com/font.woff2",
+        "https://example.com/banner.PNG",
+        "https://example.com/page",
+      ]);
+      data.options.rules.ignore_robots_txt = true;
+      data.options.include_extensions = include_extensions;
+      data.options.exclude_extensions = exclude_extensions;
+      data.options.include_file_types = include_file_types;
+      data
+    };
+
+    let result = _filter_links(call(None, None, None), None).unwrap();
+    assert_eq!(
+      result.links,
+      vec![
+        "https://example.com/report.pdf",
+        "https://example.com/data.csv",
+        "https://example.com/banner.PNG",
+        "https://example.com/page"
+      ]
+    );
+    assert_eq!(
+      result.denial_reasons["https://example.com/slides.pptx"],
+      FILE_TYPE
+    );
+
+    let result = _filter_links(call(None, Some(vec![".png".to_string()]), None), None).unwrap();
+    assert_eq!(
+      result.denial_reasons["https://example.com/banner.PNG"],
+      FILE_TYPE
+    );
+
+    let result = _filter_links(call(None, Some(vec![".pdf".to_string()]), None), None).unwrap();
+    assert_eq!(
+      result.denial_reasons["https://example.com/report.pdf"],
+      FILE_TYPE
+    );
+
+    let result = _filter_links(
+      call(
+        None,
+        Some(vec!["csv".to_string()]),
+        Some(vec!["documents".to_string()]),
+      ),
+      None,
+    )
+    .unwrap();
+    assert_eq!(
+      result.links,
+      vec![
+        "https://example.com/report.pdf",
+        "https://example.com/slides.pptx",
+        "https://example.com/banner.PNG",
+        "https://example.com/page"
+      ]
+    );
+    assert_eq!(
+      result.denial_reasons["https://example.com/style.css"],
+      FILE_TYPE
+    );
+
+    let result = _filter_links(
+      call(
+        Some(vec![".csv".to_string()]),
+        None,
+        Some(vec!["image/*".to_string(), "application/pdf".to_string()]),
+      ),
+      None,
+    )
+    .unwrap();
+    assert_eq!(
+      result.links,
+      vec![
+        "https://example.com/report.pdf",
+        "https://example.com/data.csv",
+        "https://example.com/photo.jpg",
+        "https://example.com/banner.PNG",
+        "https://example.com/page"
+      ]
+    );
+
+    let result = _filter_links(call(None, None, Some(vec!["text/css".to_string()])), None).unwrap();
+    assert!(result
+      .denial_reasons
+      .contains_key("https://example.com/style.css"));
+
+    assert!(_filter_links(call(None, None, Some(vec!["fonts".to_string()])), None).is_err());
   }
 }
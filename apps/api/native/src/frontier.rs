@@ -0,0 +1,652 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use serde::Deserialize;
+use std::{
+  cmp::{Ordering, Reverse},
+  collections::{BinaryHeap, HashMap},
+  time::{Duration, Instant},
+};
+use url::Url;
+
+use crate::crawler::{FilterLinksResult, LinkFilter, LinkFilterOptions, DEPTH_LIMIT};
+use crate::seen::{SeenSet, SeenSetOptions};
+
+#[derive(Deserialize)]
+#[napi(object)]
+pub struct CrawlFrontierOptions {
+  /// Rules applied to every inserted link. `limit` caps the total number of
+  /// URLs the frontier ever accepts. Links are always deduplicated.
+  pub filter: LinkFilterOptions,
+  /// Maximum popped-but-not-completed URLs per host. Defaults to 1.
+  pub max_concurrency_per_host: Option<u32>,
+  /// Minimum delay between two requests to the same host, in milliseconds.
+  pub crawl_delay_ms: Option<f64>,
+  /// Use the robots.txt `Crawl-delay` for the base URL's host when it is
+  /// longer than `crawl_delay_ms`. Defaults to true.
+  pub respect_robots_crawl_delay: Option<bool>,
+  /// Links discovered deeper than this many hops from the start are denied.
+  pub max_discovery_depth: Option<u32>,
+  /// Seen-URL set configuration. Defaults to an exact set spilling to disk.
+  pub seen: Option<SeenSetOptions>,
+}
+
+#[derive(Debug)]
+#[napi(object)]
+pub struct FrontierEntry {
+  pub url: String,
+  pub host: String,
+  /// Number of hops from the start URL.
+  pub depth: u32,
+  pub priority: f64,
+}
+
+struct QueuedUrl {
+  url: String,
+  depth: u32,
+  priority: f64,
+  sequence: u64,
+}
+
+impl PartialEq for QueuedUrl {
+  fn eq(&self, other: &Self) -> bool {
+    self.cmp(other) == Ordering::Equal
+  }
+}
+
+impl Eq for QueuedUrl {}
+
+impl PartialOrd for QueuedUrl {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl Ord for QueuedUrl {
+  /// Highest priority first, then first in first out.
+  fn cmp(&self, other: &Self) -> Ordering {
+    self
+      .priority
+      .total_cmp(&other.priority)
+      .then_with(|| other.sequence.cmp(&self.sequence))
+  }
+}
+
+/// Host that can be popped from now, keyed by the URL at the head of its
+/// queue.
+struct ReadyHost {
+  priority: f64,
+  sequence: u64,
+  host: String,
+  version: u64,
+}
+
+impl PartialEq for ReadyHost {
+  fn eq(&self, other: &Self) -> bool {
+    self.cmp(other) == Ordering::Equal
+  }
+}
+
+impl Eq for ReadyHost {}
+
+impl PartialOrd for ReadyHost {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl Ord for ReadyHost {
+  /// Same order as the `QueuedUrl` at the head of the host's queue.
+  fn cmp(&self, other: &Self) -> Ordering {
+    self
+      .priority
+      .total_cmp(&other.priority)
+      .then_with(|| other.sequence.cmp(&self.sequence))
+  }
+}
+
+/// Hosts keyed by the instant their crawl-delay ends, earliest first.
+type TimedHosts = BinaryHeap<Reverse<(Instant, u64, String)>>;
+
+#[derive(Default)]
+struct HostQueue {
+  queue: BinaryHeap<QueuedUrl>,
+  in_flight: u32,
+  delay: Option<Duration>,
+  next_allowed: Option<Instant>,
+  /// Bumped on every reschedule; heap entries with an older version are
+  /// stale and skipped.
+  version: u64,
+}
+
+#[inline]
+fn host_key(url: &Url) -> String {
+  let host = url.host_str().unwrap_or("").to_lowercase();
+  match url.port() {
+    Some(port) => format!("{host}:{port}"),
+    None => host,
+  }
+}
+
+#[inline]
+fn duration_from_ms(ms: f64) -> Option<Duration> {
+  (ms.is_finite() && ms > 0.0).then(|| Duration::from_secs_f64(ms / 1000.0))
+}
+
+/// Crawl scheduling state kept on the native side: the seen set, per-host
+/// priority queues, per-host concurrency and crawl-delay. Links are checked
+/// with `filter_links` rules when they are pushed.
+#[napi]
+pub struct CrawlFrontier {
+  filter: LinkFilter,
+  limit: usize,
+  seen: SeenSet,
+  hosts: HashMap<String, HostQueue>,
+  /// Hosts below their concurrency limit and past their crawl-delay, with
+  /// queued URLs. Every host is in at most one of `ready`, `delayed` and
+  /// `expiring` under its current version.
+  ready: BinaryHeap<ReadyHost>,
+  /// Hosts with queued URLs waiting out their crawl-delay.
+  delayed: TimedHosts,
+  /// Idle hosts dropped once their crawl-delay ends.
+  expiring: TimedHosts,
+  /// Crawl-delays set with `set_host_delay`, kept apart from `hosts` so they
+  /// outlive idle host queues.
+  host_delays: HashMap<String, Option<Duration>>,
+  /// Popped URLs not yet completed, with their host.
+  in_flight_urls: HashMap<String, String>,
+  max_concurrency_per_host: u32,
+  default_delay: Option<Duration>,
+  base_host: String,
+  base_host_delay: Option<Duration>,
+  max_discovery_depth: Option<u32>,
+  sequence: u64,
+  pending: u32,
+}
+
+impl CrawlFrontier {
+  fn compile(options: CrawlFrontierOptions) -> std::result::Result<Self, String> {
+    let mut filter_options = options.filter;
+    let limit = filter_options
+      .limit
+      .map_or(usize::MAX, |x| x.max(0) as usize);
+    filter_options.deduplicate = Some(true);
+
+    let base_host = Url::parse(&filter_options.rules.base_url)
+      .map(|x| host_key(&x))
+      .map_err(|e| format!("Base URL parse error: {e}"))?;
+    let filter = LinkFilter::compile(filter_options)?;
+    let seen = SeenSet::compile(options.seen.unwrap_or_default())?;
+
+    let default_delay = options.crawl_delay_ms.and_then(duration_from_ms);
+    let robots_delay = if options.respect_robots_crawl_delay.unwrap_or(true) {
+      filter
+        .robots_crawl_delay()
+        .and_then(|x| duration_from_ms(x * 1000.0))
+    } else {
+      None
+    };
+
+    Ok(CrawlFrontier {
+      filter,
+      limit,
+      seen,
+      hosts: HashMap::new(),
+      ready: BinaryHeap::new(),
+      delayed: BinaryHeap::new(),
+      expiring: BinaryHeap::new(),
+      host_delays: HashMap::new(),
+      in_flight_urls: HashMap::new(),
+      max_concurrency_per_host: options.max_concurrency_per_host.unwrap_or(1).max(1),
+      default_delay,
+      base_host,
+      base_host_delay: default_delay.max(robots_delay),
+      max_discovery_depth: options.max_discovery_depth,
+      sequence: 0,
+      pending: 0,
+    })
+  }
+
+  fn _push(&mut self, links: Vec<String>, priority: f64, depth: u32) -> FilterLinksResult {
+    if self.max_discovery_depth.is_some_and(|x| depth > x) {
+      let denial_reasons = links
+        .into_iter()
+        .map(|link| (link, DEPTH_LIMIT.to_string()))
+        .collect();
+      return FilterLinksResult {
+        links: Vec::new(),
+        denial_reasons,
+        denial_details: None,
+        invalid_patterns: HashMap::new(),
+      };
+    }
+
+    let now = Instant::now();
+    let remaining = self.limit.saturating_sub(self.seen.len() as usize);
+    let result = self
+      .filter
+      .filter_batch(links, remaining, Some(&mut self.seen));
+
+    for link in &result.links {
+      let Ok(url) = Url::parse(link) else {
+        continue;
+      };
+      let host = host_key(&url);
+      let delay = match self.host_delays.get(&host) {
+        Some(delay) => *delay,
+        None if host == self.base_host => self.base_host_delay,
+        None => self.default_delay,
+      };
+      let queue = self.hosts.entry(host.clone()).or_insert_with(|| HostQueue {
+        delay,
+        ..Default::default()
+      });
+
+      self.sequence += 1;
+      let queued = QueuedUrl {
+        url: link.clone(),
+        depth,
+        priority,
+        sequence: self.sequence,
+      };
+      let is_head = queue.queue.peek().is_none_or(|head| queued > *head);
+      queue.queue.push(queued);
+      self.pending += 1;
+      if is_head {
+        self.schedule(&host, now);
+      }
+    }
+
+    result
+  }
+
+  #[inline]
+  fn is_current(&self, host: &str, version: u64) -> bool {
+    self.hosts.get(host).is_some_and(|x| x.version == version)
+  }
+
+  /// Put `host` in the heap matching its state after a push, pop or
+  /// complete, or drop it once idle. Its older heap entries become stale.
+  fn schedule(&mut self, host: &str, now: Instant) {
+    let Some(queue) = self.hosts.get_mut(host) else {
+      return;
+    };
+    queue.version += 1;
+    let version = queue.version;
+    let delayed_until = queue.next_allowed.filter(|x| *x > now);
+
+    if let Some(head) = queue.queue.peek() {
+      // Hosts at their concurrency limit are rescheduled by `complete`
+      if queue.in_flight < self.max_concurrency_per_host {
+        match delayed_until {
+          Some(at) => self.delayed.push(Reverse((at, version, host.to_string()))),
+          None => self.ready.push(ReadyHost {
+            priority: head.priority,
+            sequence: head.sequence,
+            host: host.to_string(),
+            version,
+          }),
+        }
+      }
+    } else if queue.in_flight == 0 {
+      // Idle queues are kept until their crawl-delay ends so it still
+      // applies to URLs pushed in the meantime
+      match delayed_until {
+        Some(at) => self.expiring.push(Reverse((at, version, host.to_string()))),
+        None => {
+          self.hosts.remove(host);
+        }
+      }
+    }
+  }
+
+  /// Reschedule the hosts whose crawl-delay ended by `now`.
+  fn promote_due(&mut self, now: Instant) {
+    while let Some((version, host)) =
+      pop_due(&mut self.delayed, now).or_else(|| pop_due(&mut self.expiring, now))
+    {
+      if self.is_current(&host, version) {
+        self.schedule(&host, now);
+      }
+    }
+  }
+
+  fn pop_at(&mut self, now: Instant) -> Option<FrontierEntry> {
+    self.promote_due(now);
+
+    while let Some(ReadyHost { host, version, .. }) = self.ready.pop() {
+      let Some(queue) = self.hosts.get_mut(&host).filter(|x| x.version == version) else {
+        continue;
+      };
+      let Some(next) = queue.queue.pop() else {
+        continue;
+      };
+      queue.in_flight += 1;
+      queue.next_allowed = queue.delay.map(|delay| now + delay);
+      self.pending -= 1;
+      self.in_flight_urls.insert(next.url.clone(), host.clone());
+      self.schedule(&host, now);
+
+      return Some(FrontierEntry {
+        url: next.url,
+        host,
+        depth: next.depth,
+        priority: next.priority,
+      });
+    }
+
+    None
+  }
+
+  fn next_ready_in_at(&mut self, now: Instant) -> Option<f64> {
+    self.promote_due(now);
+
+    while let Some(x) = self.ready.peek() {
+      if self.is_current(&x.host, x.version) {
+        return Some(0.0);
+      }
+      self.ready.pop();
+    }
+    while let Some(Reverse((at, version, host))) = self.delayed.peek() {
+      if self.is_current(host, *version) {
+        return Some(at.saturating_duration_since(now).as_secs_f64() * 1000.0);
+      }
+      self.delayed.pop();
+    }
+
+    None
+  }
+}
+
+fn pop_due(heap: &mut TimedHosts, now: Instant) -> Option<(u64, String)> {
+  if heap.peek().is_none_or(|Reverse((at, _, _))| *at > now) {
+    return None;
+  }
+  heap
+    .pop()
+    .map(|Reverse((_, version, host))| (version, host))
+}
+
+#[napi]
+impl CrawlFrontier {
+  #[napi(constructor)]
+  pub fn new(options: CrawlFrontierOptions) -> Result<Self> {
+    CrawlFrontier::compile(options)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Crawl frontier error: {e}")))
+  }
+
+  /// Filter `links` and queue the accepted ones. `depth` is the number of hops
+  /// from the start URL, and higher `priority` URLs are popped first.
+  #[napi]
+  pub fn push(
+    &mut self,
+    links: Vec<String>,
+    priority: Option<f64>,
+    depth: Option<u32>,
+  ) -> FilterLinksResult {
+    self._push(links, priority.unwrap_or(0.0), depth.unwrap_or(0))
+  }
+
+  /// Take the highest-priority URL from a host that is below its concurrency
+  /// limit and past its crawl-delay. Call `complete` once it was fetched.
+  #[napi]
+  pub fn pop(&mut self) -> Option<FrontierEntry> {
+    self.pop_at(Instant::now())
+  }
+
+  /// Release the concurrency slot taken by a popped URL. URLs that are not
+  /// in flight are ignored.
+  #[napi]
+  pub fn complete(&mut self, url: String) {
+    let Some(host) = self.in_flight_urls.remove(&url) else {
+      return;
+    };
+    if let Some(queue) = self.hosts.get_mut(&host) {
+      queue.in_flight -= 1;
+    }
+    self.schedule(&host, Instant::now());
+  }
+
+  /// Milliseconds until `pop` can return a URL: 0 when one is ready now, or
+  /// `null` when nothing is queued or every host waits on `complete`.
+  #[napi]
+  pub fn next_ready_in(&mut self) -> Option<f64> {
+    self.next_ready_in_at(Instant::now())
+  }
+
+  /// Override the crawl-delay for the host of `url`, e.g. from its own
+  /// robots.txt.
+  #[napi]
+  pub fn set_host_delay(&mut self, url: String, delay_ms: f64) -> Result<()> {
+    let url = Url::parse(&url)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Set host delay error: {e}")))?;
+    let host = host_key(&url);
+    let delay = duration_from_ms(delay_ms);
+    if let Some(queue) = self.hosts.get_mut(&host) {
+      queue.delay = delay;
+    }
+    self.host_delays.insert(host, delay);
+    Ok(())
+  }
+
+  /// Whether `url` was accepted before, compared in canonical form.
+  #[napi]
+  pub fn has_seen(&self, url: String) -> bool {
+    self
+      .filter
+      .canonical_link(&url)
+      .is_some_and(|x| self.seen.contains(&x))
+  }
+
+  /// Number of queued URLs.
+  #[napi(getter)]
+  pub fn pending(&self) -> u32 {
+    self.pending
+  }
+
+  /// Number of popped URLs not yet completed.
+  #[napi(getter)]
+  pub fn in_flight(&self) -> u32 {
+    self.in_flight_urls.len() as u32
+  }
+
+  /// Number of URLs ever accepted.
+  #[napi(getter)]
+  pub fn seen_count(&self) -> u32 {
+    self.seen.len() as u32
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::crawler::UrlRuleOptions;
+
+  fn options(robots_txt: &str) -> CrawlFrontierOptions {
+    CrawlFrontierOptions {
+      filter: LinkFilterOptions {
+        rules: UrlRuleOptions {
+          base_url: "https://example.com".to_string(),
+          excludes: vec!["^/private".to_string()],
+          include_globs: None,
+          exclude_globs: None,
+          depth_strategy: None,
+          ignore_robots_txt: false,
+          robots_txt: robots_txt.to_string(),
+          allow_external_content_links: false,
+          allow_subdomains: true,
+          user_agents: None,
+          blocked_domains: None,
+          allowed_domains: None,
+        },
+        limit: None,
+        max_depth: 10,
+        initial_url: "https://example.com".to_string(),
+        regex_on_full_url: false,
+        includes: vec![],
+        allow_backward_crawling: true,
+        include_extensions: None,
+        exclude_extensions: None,
+        include_file_types: None,
+        explain_denials: None,
+        deduplicate: None,
+      },
+      max_concurrency_per_host: None,
+      crawl_delay_ms: None,
+      respect_robots_crawl_delay: None,
+      max_discovery_depth: Some(2),
+      seen: None,
+    }
+  }
+
+  #[test]
+  fn test_frontier_filters_and_deduplicates() {
+    let mut frontier = CrawlFrontier::compile(options("")).unwrap();
+
+    let result = frontier._push(
+      vec![
+        "/a".to_string(),
+        "/a/".to_string(),
+        "/private/x".to_string(),
+        "https://other.com/".to_string(),
+      ],
+      0.0,
+      1,
+    );
+    assert_eq!(result.links, vec!["https://example.com/a"]);
+    assert_eq!(result.denial_reasons["/a/"], "DUPLICATE");
+    assert_eq!(result.denial_reasons["/private/x"], "EXCLUDE_PATTERN");
+
+    let result = frontier._push(vec!["https://example.com/a".to_string()], 0.0, 1);
+    assert_eq!(result.denial_reasons["https://example.com/a"], "DUPLICATE");
+
+    let result = frontier._push(vec!["/b".to_string()], 0.0, 3);
+    assert_eq!(result.denial_reasons["/b"], DEPTH_LIMIT);
+
+    assert_eq!(frontier.pending(), 1);
+    assert!(frontier.has_seen("https://example.com/a".to_string()));
+    assert!(frontier.has_seen("https://EXAMPLE.com/a/?utm_source=x".to_string()));
+    assert!(frontier.has_seen("/a".to_string()));
+    assert!(!frontier.has_seen("/b".to_string()));
+  }
+
+  #[test]
+  fn test_frontier_priority_and_concurrency() {
+    let mut frontier = CrawlFrontier::compile(options("")).unwrap();
+    let now = Instant::now();
+
+    frontier._push(vec!["/low".to_string()], 0.0, 1);
+    frontier._push(vec!["/high".to_string()], 5.0, 1);
+    frontier._push(vec!["https://docs.example.com/x".to_string()], 1.0, 1);
+
+    let first = frontier.pop_at(now).unwrap();
+    assert_eq!(first.url, "https://example.com/high");
+    assert_eq!(first.priority, 5.0);
+
+    // example.com is busy, so the subdomain goes next
+    let second = frontier.pop_at(now).unwrap();
+    assert_eq!(second.host, "docs.example.com");
+    assert!(frontier.pop_at(now).is_none());
+    assert_eq!(frontier.next_ready_in_at(now), None);
+
+    frontier.complete("https://example.com/unknown".to_string());
+    assert_eq!(frontier.in_flight(), 2);
+    frontier.complete(first.url.clone());
+    frontier.complete(first.url);
+    assert_eq!(frontier.in_flight(), 1);
+    assert_eq!(frontier.next_ready_in_at(now), Some(0.0));
+    assert_eq!(frontier.pop_at(now).unwrap().url, "https://example.com/low");
+    assert_eq!(frontier.pending(), 0);
+    assert_eq!(frontier.in_flight(), 2);
+  }
+
+  #[test]
+  fn test_frontier_crawl_delay() {
+    let mut frontier = CrawlFrontier::compile(options("User-agent: *\nCrawl-delay: 2\n")).unwrap();
+    let now = Instant::now();
+
+    frontier._push(vec!["/a".to_string(), "/b".to_string()], 0.0, 1);
+    let first = frontier.pop_at(now).unwrap();
+    frontier.complete(first.url);
+
+    assert!(frontier.pop_at(now).is_none());
+    assert_eq!(frontier.next_ready_in_at(now), Some(2000.0));
+    assert!(frontier.pop_at(now + Duration::from_millis(1999)).is_none());
+    assert_eq!(
+      frontier.pop_at(now + Duration::from_secs(2)).unwrap().url,
+      "https://example.com/b"
+    );
+  }
+
+  #[test]
+  fn test_frontier_host_delay_and_pruning() {
+    let mut frontier = CrawlFrontier::compile(options("")).unwrap();
+    let now = Instant::now();
+
+    frontier
+      .set_host_delay("https://docs.example.com:8443/".to_string(), 1000.0)
+      .unwrap();
+    assert!(frontier.set_host_delay("docs".to_string(), 1000.0).is_err());
+
+    frontier._push(
+      vec![
+        "https://docs.example.com:8443/a".to_string(),
+        "https://docs.example.com:8443/b".to_string(),
+      ],
+      0.0,
+      1,
+    );
+    let first = frontier.pop_at(now).unwrap();
+    assert_eq!(first.host, "docs.example.com:8443");
+    frontier.complete(first.url);
+    assert_eq!(frontier.next_ready_in_at(now), Some(1000.0));
+
+    let later = now + Duration::from_secs(1);
+    let second = frontier.pop_at(later).unwrap();
+    frontier.complete(second.url);
+    assert!(frontier.pop_at(later + Duration::from_secs(1)).is_none());
+    assert!(frontier.hosts.is_empty());
+
+    // The override outlives the pruned queue.
+    frontier._push(vec!["https://docs.example.com:8443/c".to_string()], 0.0, 1);
+    assert_eq!(
+      frontier.hosts["docs.example.com:8443"].delay,
+      Some(Duration::from_secs(1))
+    );
+  }
+
+  #[test]
+  fn test_frontier_orders_hosts_by_head() {
+    let mut frontier = CrawlFrontier::compile(options("")).unwrap();
+    let now = Instant::now();
+
+    let links: Vec<String> = (0..50)
+      .map(|x| format!("https://h{x}.example.com/"))
+      .collect();
+    frontier._push(links, 1.0, 1);
+    // A better URL replaces the head of an already scheduled host
+    frontier._push(vec!["https://h7.example.com/top".to_string()], 9.0, 1);
+    frontier._push(vec!["https://h3.example.com/mid".to_string()], 5.0, 1);
+
+    assert_eq!(
+      frontier.pop_at(now).unwrap().url,
+      "https://h7.example.com/top"
+    );
+    assert_eq!(
+      frontier.pop_at(now).unwrap().url,
+      "https://h3.example.com/mid"
+    );
+    // Ties keep push order, and busy hosts are skipped
+    let hosts: Vec<String> = std::iter::from_fn(|| frontier.pop_at(now))
+      .map(|x| x.host)
+      .collect();
+    assert_eq!(hosts.len(), 48);
+    assert_eq!(hosts[0], "h0.example.com");
+    assert_eq!(hosts[3], "h4.example.com");
+    assert_eq!(frontier.pending(), 2);
+    assert_eq!(frontier.next_ready_in_at(now), None);
+
+    frontier.complete("https://h3.example.com/mid".to_string());
+    assert_eq!(frontier.pop_at(now).unwrap().url, "https://h3.example.com/");
+    assert!(frontier.pop_at(now).is_none());
+  }
+}
@@ -1,10 +1,21 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+  borrow::Cow,
+  cell::{Cell, RefCell},
+  collections::{HashMap, HashSet},
+  rc::Rc,
+  sync::LazyLock,
+};
 
 use kuchikiki::{iter::NodeEdge, parse_html, traits::TendrilSink, NodeRef};
+use lol_html::{
+  element, html_content::Element, ElementContentHandlers, HtmlRewriter, Selector, Settings,
+};
+use napi::bindgen_prelude::Buffer;
 use napi_derive::napi;
 use nodesig::{get_node_signature, SignatureMode};
+use regex::{Captures, Regex};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Map, Value};
 use url::Url;
 
 use crate::utils::to_napi_err;
@@ -112,6 +123,50 @@ macro_rules! insert_meta_property {
   };
 }
 
+const FEED_TYPES: [&str; 3] = [
+  "application/rss+xml",
+  "application/atom+xml",
+  "application/feed+json",
+];
+
+const TWITTER_META: [(&str, &str); 6] = [
+  ("twitter:card", "twitterCard"),
+  ("twitter:site", "twitterSite"),
+  ("twitter:creator", "twitterCreator"),
+  ("twitter:title", "twitterTitle"),
+  ("twitter:description", "twitterDescription"),
+  ("twitter:image", "twitterImage"),
+];
+
+/// Parse `<meta http-equiv="refresh">` content like `5; url=/next` into
+/// `{ delay, url? }`.
+fn _parse_meta_refresh(content: &str) -> Option<Value> {
+  let content = content.trim();
+  let delay_end = content
+    .find(|c: char| !c.is_ascii_digit() && c != '.')
+    .unwrap_or(content.len());
+  let delay: u64 = content[..delay_end].split('.').next()?.parse().ok()?;
+
+  let mut url = content[delay_end..]
+    .trim_start_matches(|c: char| c.is_ascii_whitespace() || c == ';' || c == ',');
+  if url.get(..3).is_some_and(|x| x.eq_ignore_ascii_case("url")) {
+    if let Some(rest) = url[3..].trim_start().strip_prefix('=') {
+      url = rest.trim_start();
+    }
+  }
+  let url = match url.chars().next() {
+    Some(quote @ ('"' | '\'')) => url[1..].split(quote).next().unwrap_or_default(),
+    _ => url,
+  }
+  .trim();
+
+  let mut refresh = json!({ "delay": delay });
+  if !url.is_empty() {
+    refresh["url"] = Value::String(url.to_string());
+  }
+  Some(refresh)
+}
+
 fn _extract_metadata(html: &str) -> Result<HashMap<String, Value>, Box<dyn std::error::Error>> {
   let document = parse_html().one(html);
   let mut out = HashMap::<String, Value>::new();
@@ -151,6 +206,88 @@ fn _extract_metadata(html: &str) -> Result<HashMap<String, Value>, Box<dyn std::
     out.insert("language".to_string(), Value::String(lang));
   }
 
+  let links: Vec<_> = document
+    .select("link[rel][href]")
+    .map_err(|_| "Failed to select links")?
+    .map(|x| x.attributes.borrow().clone())
+    .collect();
+  let has_rel = |attrs: &kuchikiki::Attributes, rels: &[&str]| {
+    attrs.get("rel").is_some_and(|x| {
+      x.split_ascii_whitespace()
+        .any(|x| rels.iter().any(|rel| x.eq_ignore_ascii_case(rel)))
+    })
+  };
+  let first_link = |rels: &[&str]| {
+    links
+      .iter()
+      .find(|x| has_rel(x, rels))
+      .and_then(|x| x.get("href").map(|x| Value::String(x.to_string())))
+  };
+
+  if let Some(href) = first_link(&["canonical"]) {
+    out.insert("canonicalUrl".to_string(), href);
+  }
+  if let Some(href) = first_link(&["next"]) {
+    out.insert("nextUrl".to_string(), href);
+  }
+  if let Some(href) = first_link(&["prev", "previous"]) {
+    out.insert("prevUrl".to_string(), href);
+  }
+
+  let mut hreflang_alternates = vec![];
+  let mut feeds = vec![];
+  for link in links.iter().filter(|x| has_rel(x, &["alternate"])) {
+    let href = link.get("href").unwrap_or_default();
+    if let Some(hreflang) = link.get("hreflang") {
+      hreflang_alternates.push(json!({ "hreflang": hreflang, "href": href }));
+    } else if let Some(feed_type) = link
+      .get("type")
+      .map(|x| x.trim().to_ascii_lowercase())
+      .filter(|x| FEED_TYPES.contains(&x.as_str()))
+    {
+      let mut feed = json!({ "href": href, "type": feed_type });
+      if let Some(title) = link.get("title") {
+        feed["title"] = Value::String(title.to_string());
+      }
+      feeds.push(feed);
+    }
+  }
+  if !hreflang_alternates.is_empty() {
+    out.insert(
+      "hreflangAlternates".to_string(),
+      Value::Array(hreflang_alternates),
+    );
+  }
+  if !feeds.is_empty() {
+    out.insert("feeds".to_string(), Value::Array(feeds));
+  }
+
+  if let Some(refresh) = document
+    .select("meta[http-equiv][content]")
+    .map_err(|_| "Failed to select http-equiv meta")?
+    .find(|x| {
+      x.attributes
+        .borrow()
+        .get("http-equiv")
+        .is_some_and(|x| x.trim().eq_ignore_ascii_case("refresh"))
+    })
+    .and_then(|x| {
+      x.attributes
+        .borrow()
+        .get("content")
+        .and_then(_parse_meta_refresh)
+    })
+  {
+    out.insert("refresh".to_string(), refresh);
+  }
+
+  for (name, key) in TWITTER_META {
+    insert_meta_name!(out, document, name, key);
+    if !out.contains_key(key) {
+      insert_meta_property!(out, document, name, key);
+    }
+  }
+
   insert_meta_property!(out, document, "og:title", "ogTitle");
   insert_meta_property!(out, document, "og:description", "ogDescription");
   insert_meta_property!(out, document, "og:url", "ogUrl");
@@ -269,6 +406,324 @@ pub fn extract_metadata(html: Option<String>) -> napi::Result<HashMap<String, Va
   _extract_metadata(&html).map_err(to_napi_err)
 }
 
+/// Prefixes from the RDFa initial context that are common on the web.
+const RDFA_PREFIXES: [(&str, &str); 7] = [
+  ("schema", "http://schema.org/"),
+  ("og", "http://ogp.me/ns#"),
+  ("dc", "http://purl.org/dc/terms/"),
+  ("dcterms", "http://purl.org/dc/terms/"),
+  ("foaf", "http://xmlns.com/foaf/0.1/"),
+  ("rdfs", "http://www.w3.org/2000/01/rdf-schema#"),
+  ("xsd", "http://www.w3.org/2001/XMLSchema#"),
+];
+
+#[derive(Serialize)]
+#[napi(object)]
+pub struct StructuredData {
+  /// Parsed `application/ld+json` blocks, as written.
+  pub json_ld: Vec<Value>,
+  /// Top-level Microdata items as `{ type, id, properties }` trees, where each
+  /// property maps to a list of strings or nested items.
+  pub microdata: Vec<Value>,
+  /// RDFa resources in the same shape as `microdata`, with `typeof` expanded
+  /// to full IRIs using `vocab` and `prefix`. Properties without a typed
+  /// subject are collected on a first item whose `id` is the page URL.
+  pub rdfa: Vec<Value>,
+  /// JSON-LD blocks that failed to parse.
+  pub errors: Vec<String>,
+}
+
+fn _structured_item(
+  types: Vec<String>,
+  id: Option<String>,
+  properties: Map<String, Value>,
+) -> Value {
+  let mut item = Map::new();
+  if !types.is_empty() {
+    item.insert("type".to_string(), types.into());
+  }
+  if let Some(id) = id {
+    item.insert("id".to_string(), id.into());
+  }
+  item.insert("properties".to_string(), Value::Object(properties));
+  Value::Object(item)
+}
+
+fn _push_property(properties: &mut Map<String, Value>, names: &str, value: Value) {
+  for name in names.split_whitespace() {
+    if let Value::Array(values) = properties
+      .entry(name.to_string())
+      .or_insert_with(|| Value::Array(vec![]))
+    {
+      values.push(value.clone());
+    }
+  }
+}
+
+/// Property value of an element, following the Microdata rules: URLs for
+/// media and links, `content`/`value`/`datetime` where they apply, text
+/// otherwise.
+fn _structured_value(node: &NodeRef, resolver: &UrlResolver) -> String {
+  let Some(element) = node.as_element() else {
+    return String::new();
+  };
+  let attributes = element.attributes.borrow();
+  let url_attribute = match &*element.name.local {
+    "meta" => return attributes.get("content").unwrap_or_default().to_string(),
+    "data" | "meter" => return attributes.get("value").unwrap_or_default().to_string(),
+    "time" if attributes.contains("datetime") => {
+      return attributes.get("datetime").unwrap_or_default().to_string()
+    }
+    "audio" | "embed" | "iframe" | "img" | "source" | "track" | "video" => "src",
+    "a" | "area" | "link" => "href",
+    "object" => "data",
+    _ => return node.text_contents().trim().to_string(),
+  };
+  attributes
+    .get(url_attribute)
+    .map(|x| resolver.resolve(x).unwrap_or_else(|_| x.to_string()))
+    .unwrap_or_default()
+}
+
+fn _microdata_item(item: &NodeRef, resolver: &UrlResolver, memory: &mut HashSet<usize>) -> Value {
+  memory.insert(_node_key(item));
+
+  let (types, id, itemref) = match item.as_element() {
+    Some(element) => {
+      let attributes = element.attributes.borrow();
+      (
+        attributes
+          .get("itemtype")
+          .map(|x| x.split_whitespace().map(String::from).collect())
+          .unwrap_or_default(),
+        attributes
+          .get("itemid")
+          .map(|x| resolver.resolve(x).unwrap_or_else(|_| x.to_string())),
+        attributes.get("itemref").map(String::from),
+      )
+    }
+    None => (vec![], None, None),
+  };
+
+  // Properties come from the item's subtree and any elements it `itemref`s
+  let mut pending: Vec<NodeRef> = item.children().collect();
+  if let Some(refs) = itemref {
+    let root = item
+      .inclusive_ancestors()
+      .last()
+      .unwrap_or_else(|| item.clone());
+    for id in refs.split_whitespace() {
+      if let Some(target) = root.descendants().find(|x| {
+        x.as_element()
+          .is_some_and(|x| x.attributes.borrow().get("id") == Some(id))
+      }) {
+        pending.push(target);
+      }
+    }
+  }
+  pending.reverse();
+
+  let mut properties = Map::new();
+  while let Some(node) = pending.pop() {
+    let Some(element) = node.as_element() else {
+      continue;
+    };
+    let (itemprop, itemscope) = {
+      let attributes = element.attributes.borrow();
+      (
+        attributes.get("itemprop").map(String::from),
+        attributes.contains("itemscope"),
+      )
+    };
+
+    if let Some(itemprop) = itemprop {
+      let value = if !itemscope {
+        Value::String(_structured_value(&node, resolver))
+      } else if memory.contains(&_node_key(&node)) {
+        Value::String("ERROR".to_string())
+      } else {
+        _microdata_item(&node, resolver, memory)
+      };
+      _push_property(&mut properties, &itemprop, value);
+    }
+    if !itemscope {
+      pending.extend(node.children().collect::<Vec<_>>().into_iter().rev());
+    }
+  }
+
+  memory.remove(&_node_key(item));
+  _structured_item(types, id, properties)
+}
+
+#[derive(Clone, Default)]
+struct RdfaContext {
+  vocab: Option<String>,
+  prefixes: HashMap<String, String>,
+}
+
+impl RdfaContext {
+  fn expand(&self, term: &str) -> String {
+    if term.contains("://") {
+      return term.to_string();
+    }
+    if let Some((prefix, reference)) = term.split_once(':') {
+      let iri = self.prefixes.get(prefix).map(|x| x.as_str()).or_else(|| {
+        RDFA_PREFIXES
+          .iter()
+          .find(|(x, _)| *x == prefix)
+          .map(|(_, iri)| *iri)
+      });
+      return match iri {
+        Some(iri) => format!("{iri}{reference}"),
+        None => term.to_string(),
+      };
+    }
+    match &self.vocab {
+      Some(vocab) => format!("{vocab}{term}"),
+      None => term.to_string(),
+    }
+  }
+}
+
+fn _rdfa_walk(
+  node: &NodeRef,
+  context: &RdfaContext,
+  properties: &mut Map<String, Value>,
+  items: &mut Vec<Value>,
+  resolver: &UrlResolver,
+) {
+  for child in node.children() {
+    let Some(element) = child.as_element() else {
+      continue;
+    };
+    let attributes = element.attributes.borrow();
+
+    let mut child_context = context.clone();
+    if let Some(vocab) = attributes.get("vocab") {
+      child_context.vocab = Some(vocab.to_string());
+    }
+    if let Some(prefix) = attributes.get("prefix") {
+      let tokens: Vec<&str> = prefix.split_whitespace().collect();
+      for pair in tokens.chunks_exact(2) {
+        if let Some(name) = pair[0].strip_suffix(':') {
+          child_context
+            .prefixes
+            .insert(name.to_string(), pair[1].to_string());
+        }
+      }
+    }
+
+    let property = attributes.get("property");
+    if let Some(types) = attributes.get("typeof") {
+      let types = types
+        .split_whitespace()
+        .map(|x| child_context.expand(x))
+        .collect();
+      let id = attributes
+        .get("resource")
+        .or_else(|| attributes.get("about"))
+        .map(|x| resolver.resolve(x).unwrap_or_else(|_| x.to_string()));
+      let mut item_properties = Map::new();
+      _rdfa_walk(
+        &child,
+        &child_context,
+        &mut item_properties,
+        items,
+        resolver,
+      );
+      let item = _structured_item(types, id, item_properties);
+      match property {
+        Some(property) => _push_property(properties, property, item),
+        None => items.push(item),
+      }
+    } else {
+      if let Some(property) = property {
+        let value = match (attributes.get("content"), attributes.get("resource")) {
+          (Some(content), _) => content.to_string(),
+          (None, Some(resource)) => resolver
+            .resolve(resource)
+            .unwrap_or_else(|_| resource.to_string()),
+          (None, None) => _structured_value(&child, resolver),
+        };
+        _push_property(properties, property, Value::String(value));
+      }
+      _rdfa_walk(&child, &child_context, properties, items, resolver);
+    }
+  }
+}
+
+fn _extract_structured_data(
+  html: &str,
+  url: &str,
+) -> Result<StructuredData, Box<dyn std::error::Error>> {
+  let document = parse_html().one(html);
+  let resolver = UrlResolver::new(&document, Url::parse(url)?)?;
+
+  let mut json_ld = vec![];
+  let mut errors = vec![];
+  for script in document
+    .select("script[type]")
+    .map_err(|_| "Failed to select scripts")?
+  {
+    let is_json_ld = script.attributes.borrow().get("type").is_some_and(|x| {
+      x.split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .eq_ignore_ascii_case("application/ld+json")
+    });
+    let text = script.text_contents();
+    if !is_json_ld || text.trim().is_empty() {
+      continue;
+    }
+    match serde_json::from_str::<Value>(text.trim()) {
+      Ok(value) => json_ld.push(value),
+      Err(e) => errors.push(format!("Invalid JSON-LD: {e}")),
+    }
+  }
+
+  let mut memory = HashSet::new();
+  let microdata = document
+    .descendants()
+    .filter(|node| {
+      node.as_element().is_some_and(|x| {
+        let attributes = x.attributes.borrow();
+        attributes.contains("itemscope") && !attributes.contains("itemprop")
+      })
+    })
+    .map(|node| _microdata_item(&node, &resolver, &mut memory))
+    .collect();
+
+  let mut rdfa = vec![];
+  let mut page_properties = Map::new();
+  _rdfa_walk(
+    &document,
+    &RdfaContext::default(),
+    &mut page_properties,
+    &mut rdfa,
+    &resolver,
+  );
+  if !page_properties.is_empty() {
+    rdfa.insert(
+      0,
+      _structured_item(vec![], Some(resolver.page_url.to_string()), page_properties),
+    );
+  }
+
+  Ok(StructuredData {
+    json_ld,
+    microdata,
+    rdfa,
+    errors,
+  })
+}
+
+/// Extract JSON-LD, Microdata and RDFa from an HTML document.
+#[napi]
+pub fn extract_structured_data(html: String, url: String) -> napi::Result<StructuredData> {
+  _extract_structured_data(&html, &url).map_err(to_napi_err)
+}
+
 const EXCLUDE_NON_MAIN_TAGS: [&str; 42] = [
   "header",
   "footer",
@@ -330,178 +785,773 @@ const FORCE_INCLUDE_MAIN_TAGS: [&str; 13] = [
   ".swoogo-agenda",
 ];
 
-#[derive(Deserialize, Serialize)]
-#[napi(object)]
-pub struct TransformHtmlOptions {
-  pub html: String,
-  pub url: String,
-  #[serde(default)]
-  pub include_tags: Vec<String>,
-  #[serde(default)]
-  pub exclude_tags: Vec<String>,
-  pub only_main_content: bool,
-  pub omce_signatures: Option<Vec<String>>,
-}
+const READABILITY_UNLIKELY: [&str; 24] = [
+  "banner",
+  "breadcrumb",
+  "combx",
+  "comment",
+  "community",
+  "cookie",
+  "disqus",
+  "footer",
+  "gdpr",
+  "header",
+  "menu",
+  "modal",
+  "nav",
+  "newsletter",
+  "pager",
+  "pagination",
+  "popup",
+  "related",
+  "share",
+  "shoutbox",
+  "sidebar",
+  "social",
+  "sponsor",
+  "subscribe",
+];
 
-struct ImageSource {
-  url: String,
-  size: f64,
-  is_x: bool,
-}
+const READABILITY_MAYBE: [&str; 6] = ["and", "article", "body", "column", "content", "main"];
 
-fn _transform_html_inner(opts: TransformHtmlOptions) -> Result<String, Box<dyn std::error::Error>> {
-  let mut document = parse_html().one(opts.html.as_ref());
-  let url = Url::parse(&_extract_base_href_from_document(
-    &document,
-    &Url::parse(&opts.url)?,
-  )?)?;
+const READABILITY_POSITIVE: [&str; 10] = [
+  "article", "body", "content", "entry", "main", "page", "post", "prose", "story", "text",
+];
 
-  if !opts.include_tags.is_empty() {
-    let new_document = parse_html().one("<div></div>");
-    let root = new_document
-      .select_first("div")
-      .map_err(|_| "Failed to select root element")?;
+const READABILITY_NEGATIVE: [&str; 14] = [
+  "ad", "comment", "extra", "footer", "foot", "masthead", "media", "meta", "promo", "related",
+  "scroll", "shoutbox", "sidebar", "widget",
+];
 
-    for x in opts.include_tags.iter() {
-      let matching_nodes: Vec<_> = document
-        .select(x)
-        .map_err(|_| "Failed to include_tags tags")?
-        .collect();
-      for tag in matching_nodes {
-        root.as_node().append(tag.as_node().clone());
-      }
-    }
+const READABILITY_BLOCK_TAGS: [&str; 12] = [
+  "address",
+  "article",
+  "blockquote",
+  "div",
+  "dl",
+  "form",
+  "ol",
+  "p",
+  "pre",
+  "section",
+  "table",
+  "ul",
+];
 
-    document = new_document;
-  }
+#[inline]
+fn _node_key(node: &NodeRef) -> usize {
+  std::rc::Rc::as_ptr(&node.0) as usize
+}
 
-  while let Ok(x) = document.select_first("head") {
-    x.as_node().detach();
-  }
-  while let Ok(x) = document.select_first("meta") {
-    x.as_node().detach();
-  }
-  while let Ok(x) = document.select_first("noscript") {
-    x.as_node().detach();
-  }
-  while let Ok(x) = document.select_first("style") {
-    x.as_node().detach();
+/// Lowercased class and id tokens of an element.
+fn _readability_tokens(node: &NodeRef) -> Vec<String> {
+  let Some(element) = node.as_element() else {
+    return vec![];
+  };
+  let attributes = element.attributes.borrow();
+  [attributes.get("class"), attributes.get("id")]
+    .into_iter()
+    .flatten()
+    .flat_map(|x| x.split(|c: char| c.is_whitespace() || c == '-' || c == '_'))
+    .filter(|x| !x.is_empty())
+    .map(|x| x.to_lowercase())
+    .collect()
+}
+
+fn _readability_class_weight(node: &NodeRef) -> f64 {
+  let tokens = _readability_tokens(node);
+  let mut weight = 0.0;
+  if tokens
+    .iter()
+    .any(|x| READABILITY_NEGATIVE.contains(&x.as_str()))
+  {
+    weight -= 25.0;
   }
-  while let Ok(x) = document.select_first("script") {
-    x.as_node().detach();
+  if tokens
+    .iter()
+    .any(|x| READABILITY_POSITIVE.contains(&x.as_str()))
+  {
+    weight += 25.0;
   }
+  weight
+}
 
-  // OMCE first
-  if opts.only_main_content {
-    if let Some(signatures) = opts.omce_signatures.as_ref() {
-      let mut nodes_to_drop: Vec<NodeRef> = Vec::new();
+#[inline]
+fn _words_length(text: &str) -> usize {
+  text.split_whitespace().map(|x| x.len() + 1).sum()
+}
 
-      let modes = signatures
-        .iter()
-        .map(|x| Into::<SignatureMode>::into(x.split(':').nth(1).unwrap().to_string()))
-        .collect::<HashSet<_>>();
+fn _text_length(node: &NodeRef) -> usize {
+  _words_length(&node.text_contents())
+}
 
-      for mode in modes {
-        let matcher = format!(":{}:", Into::<String>::into(mode.clone()));
-        let signatures = signatures
-          .iter()
-          .filter(|x| x.contains(&matcher))
-          .cloned()
-          .collect::<HashSet<_>>();
+/// Text, link text and comma counts of an element, leaving out unlikely
+/// subtrees.
+#[derive(Default, Clone, Copy)]
+struct ReadabilityStats {
+  text_length: usize,
+  link_length: usize,
+  commas: usize,
+}
 
-        for edge in document.traverse() {
-          match edge {
-            NodeEdge::Start(_) => {}
-            NodeEdge::End(node) => {
-              if node.as_element().is_none() {
-                continue;
-              }
-              if node.text_contents().trim().is_empty() {
-                continue;
-              }
+impl ReadabilityStats {
+  #[inline]
+  fn link_density(&self) -> f64 {
+    if self.text_length == 0 {
+      0.0
+    } else {
+      self.link_length as f64 / self.text_length as f64
+    }
+  }
+}
 
-              let signature = get_node_signature(&node, mode.clone());
-              if signatures.contains(&signature) {
-                nodes_to_drop.push(node);
-              }
-            }
-          }
+/// Stats of every element under `body`, computed in one bottom-up pass.
+fn _readability_stats(
+  body: &NodeRef,
+  unlikely: &HashSet<usize>,
+) -> HashMap<usize, ReadabilityStats> {
+  let mut stats = HashMap::new();
+  let mut stack = vec![ReadabilityStats::default()];
+  for edge in body.traverse() {
+    match edge {
+      NodeEdge::Start(node) => {
+        if node.as_element().is_some() {
+          stack.push(ReadabilityStats::default());
+        } else if let (Some(text), Some(top)) = (node.as_text(), stack.last_mut()) {
+          let text = text.borrow();
+          top.text_length += _words_length(&text);
+          top.commas += text.matches(',').count();
         }
       }
-
-      for node in nodes_to_drop {
-        node.detach();
+      NodeEdge::End(node) => {
+        let Some(element) = node.as_element() else {
+          continue;
+        };
+        let mut own = stack.pop().unwrap_or_default();
+        if &*element.name.local == "a" {
+          own.link_length = own.text_length;
+        }
+        let key = _node_key(&node);
+        if let (false, Some(parent)) = (unlikely.contains(&key), stack.last_mut()) {
+          parent.text_length += own.text_length;
+          parent.link_length += own.link_length;
+          parent.commas += own.commas;
+        }
+        stats.insert(key, own);
       }
     }
   }
+  stats
+}
+
+fn _readability_tag_score(node: &NodeRef) -> f64 {
+  let tag = node
+    .as_element()
+    .map(|x| x.name.local.to_string())
+    .unwrap_or_default();
+  let base = match tag.as_str() {
+    "div" | "article" | "main" | "section" => 5.0,
+    "pre" | "td" | "blockquote" => 3.0,
+    "address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form" => -3.0,
+    "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+    _ => 0.0,
+  };
+  base + _readability_class_weight(node)
+}
+
+/// Keep only the highest-scoring content block (and related siblings) in the
+/// body, scoring paragraphs by text length and commas and discounting link
+/// density, like Mozilla's Readability. Returns false when no block scored, so
+/// the caller can fall back to the selector list.
+fn _readability_main_content(
+  document: &NodeRef,
+  removals: &mut RemovalLog,
+) -> Result<bool, Box<dyn std::error::Error>> {
+  let Ok(body) = document.select_first("body") else {
+    return Ok(false);
+  };
+  let body = body.as_node().clone();
+
+  // Unlikely candidates, unless they also look like content. Nothing is
+  // detached until a top candidate was found.
+  let unlikely: Vec<NodeRef> = body
+    .descendants()
+    .filter(|node| {
+      let Some(element) = node.as_element() else {
+        return false;
+      };
+      if matches!(&*element.name.local, "nav" | "aside" | "footer") {
+        return true;
+      }
+      if matches!(&*element.name.local, "body" | "article" | "main" | "a") {
+        return false;
+      }
+      let tokens = _readability_tokens(node);
+      tokens
+        .iter()
+        .any(|x| READABILITY_UNLIKELY.contains(&x.as_str()))
+        && !tokens
+          .iter()
+          .any(|x| READABILITY_MAYBE.contains(&x.as_str()))
+    })
+    .collect();
+  let unlikely_keys: HashSet<usize> = unlikely.iter().map(_node_key).collect();
+  let hidden: HashSet<usize> = unlikely
+    .iter()
+    .flat_map(|node| node.inclusive_descendants())
+    .map(|node| _node_key(&node))
+    .collect();
+  let stats = _readability_stats(&body, &unlikely_keys);
+  let stats_of = |node: &NodeRef| stats.get(&_node_key(node)).copied().unwrap_or_default();
+
+  let mut scores: HashMap<usize, (NodeRef, f64)> = HashMap::new();
+  let paragraphs: Vec<NodeRef> = body
+    .descendants()
+    .filter(|node| !hidden.contains(&_node_key(node)))
+    .filter(|node| match node.as_element() {
+      Some(element) => match &*element.name.local {
+        "p" | "td" | "pre" => true,
+        "div" => !node.children().any(|child| {
+          child
+            .as_element()
+            .is_some_and(|x| READABILITY_BLOCK_TAGS.contains(&&*x.name.local))
+        }),
+        _ => false,
+      },
+      None => false,
+    })
+    .collect();
 
-  for x in opts.exclude_tags.iter() {
-    while let Ok(x) = document.select_first(x) {
-      x.as_node().detach();
+  for paragraph in paragraphs {
+    let paragraph_stats = stats_of(&paragraph);
+    if paragraph_stats.text_length < 25 {
+      continue;
     }
-  }
 
-  if opts.only_main_content {
-    for x in EXCLUDE_NON_MAIN_TAGS.iter() {
-      let x: Vec<_> = document
-        .select(x)
-        .map_err(|_| "Failed to select tags")?
-        .collect();
-      for tag in x {
-        if !FORCE_INCLUDE_MAIN_TAGS.iter().any(|x| {
-          tag
-            .as_node()
-            .select(x)
-            .is_ok_and(|mut x| x.next().is_some())
-        }) {
-          tag.as_node().detach();
-        }
+    let score =
+      1.0 + paragraph_stats.commas as f64 + (paragraph_stats.text_length as f64 / 100.0).min(3.0);
+    for (level, ancestor) in paragraph.ancestors().take(3).enumerate() {
+      if ancestor.as_element().is_none() {
+        break;
       }
+      let divider = match level {
+        0 => 1.0,
+        1 => 2.0,
+        _ => 6.0,
+      };
+      let entry = scores
+        .entry(_node_key(&ancestor))
+        .or_insert_with(|| (ancestor.clone(), _readability_tag_score(&ancestor)));
+      entry.1 += score / divider;
     }
   }
 
-  let srcset_images: Vec<_> = document
-    .select("img[srcset]")
-    .map_err(|_| "Failed to select srcset images")?
-    .collect();
-  for img in srcset_images {
-    let mut sizes: Vec<ImageSource> = img
-      .attributes
-      .borrow()
-      .get("srcset")
-      .ok_or("Failed to get srcset")?
-      .split(',')
-      .filter_map(|x| {
-        let tok: Vec<&str> = x.trim().split(' ').collect();
-        let last_token = tok[tok.len() - 1];
-        let (last_token, last_token_used) = if tok.len() > 1
-          && !last_token.is_empty()
-          && (last_token.ends_with('x') || last_token.ends_with('w'))
-        {
-          (last_token, true)
-        } else {
-          ("1x", false)
-        };
+  // Candidates in document order, so ties go to the earliest one
+  let Some((top, top_score)) = body
+    .inclusive_descendants()
+    .filter_map(|node| scores.get(&_node_key(&node)))
+    .map(|(node, score)| (node.clone(), score * (1.0 - stats_of(node).link_density())))
+    .reduce(|best, x| if x.1 > best.1 { x } else { best })
+  else {
+    return Ok(false);
+  };
 
-        if let Some((last_index, _)) = last_token.char_indices().last() {
-          if let Ok(parsed_size) = last_token[..last_index].parse() {
-            Some(ImageSource {
-              url: if last_token_used {
-                tok[0..tok.len() - 1].join(" ")
-              } else {
-                tok.join(" ")
-              },
-              size: parsed_size,
-              is_x: last_token.ends_with('x'),
-            })
-          } else {
-            None
-          }
-        } else {
-          None
-        }
-      })
-      .collect();
+  for node in &unlikely {
+    removals.detach("readability", "unlikely", node);
+  }
+
+  if _node_key(&top) == _node_key(&body) {
+    return Ok(true);
+  }
+
+  // Siblings of the top candidate that score well, or read like paragraphs
+  let threshold = (top_score * 0.2).max(10.0);
+  let mut content = vec![];
+  for sibling in top.parent().map(|x| x.children()).into_iter().flatten() {
+    let sibling_stats = stats_of(&sibling);
+    let keep = if _node_key(&sibling) == _node_key(&top) {
+      true
+    } else if let Some((_, score)) = scores.get(&_node_key(&sibling)) {
+      score * (1.0 - sibling_stats.link_density()) >= threshold
+    } else if sibling.as_element().is_some_and(|x| &*x.name.local == "p") {
+      let link_density = sibling_stats.link_density();
+      (sibling_stats.text_length > 80 && link_density < 0.25)
+        || (sibling_stats.text_length > 0
+          && link_density == 0.0
+          && sibling.text_contents().contains(". "))
+    } else {
+      false
+    };
+    if keep {
+      content.push(sibling);
+    }
+  }
+
+  // Remove link-heavy blocks left inside the content
+  for node in &content {
+    let link_heavy: Vec<NodeRef> = node
+      .descendants()
+      .filter(|x| {
+        x.as_element().is_some_and(|element| {
+          matches!(
+            &*element.name.local,
+            "div" | "section" | "ul" | "ol" | "table" | "form"
+          )
+        }) && {
+          let link_density = stats_of(x).link_density();
+          link_density > 0.5 || (_readability_class_weight(x) < 0.0 && link_density > 0.2)
+        }
+      })
+      .collect();
+    for x in link_heavy {
+      removals.detach("readability", "link_density", &x);
+    }
+  }
+
+  let kept: HashSet<usize> = content.iter().map(_node_key).collect();
+  for child in body.children().collect::<Vec<_>>() {
+    if kept.contains(&_node_key(&child))
+      || (child.as_element().is_none() && child.text_contents().trim().is_empty())
+    {
+      child.detach();
+    } else {
+      removals.detach("readability", "low_score", &child);
+    }
+  }
+  for node in content {
+    body.append(node);
+  }
+
+  Ok(true)
+}
+
+#[derive(Deserialize, Serialize)]
+#[napi(object)]
+pub struct TransformHtmlOptions {
+  pub html: String,
+  pub url: String,
+  #[serde(default)]
+  pub include_tags: Vec<String>,
+  #[serde(default)]
+  pub exclude_tags: Vec<String>,
+  pub only_main_content: bool,
+  pub omce_signatures: Option<Vec<String>>,
+  /// How `only_main_content` finds the main content: `"selectors"` (default)
+  /// removes `EXCLUDE_NON_MAIN_TAGS`, `"readability"` keeps the block with the
+  /// best text/link density score.
+  pub main_content_mode: Option<String>,
+  /// Selectors removed by `only_main_content` in addition to `EXCLUDE_NON_MAIN_TAGS`.
+  pub remove_selectors: Option<Vec<String>>,
+  /// Selectors that keep an element (and its ancestors) from being removed by
+  /// `only_main_content`, in addition to `FORCE_INCLUDE_MAIN_TAGS`.
+  pub force_include_selectors: Option<Vec<String>>,
+  /// Use only the selectors given above instead of extending the defaults.
+  pub replace_default_selectors: Option<bool>,
+  /// Besides `img[src]` and `a[href]`, also absolutize `srcset`, media and
+  /// iframe sources, `video[poster]`, `link[href]`, `form[action]` and
+  /// `url()` in inline styles.
+  pub absolutize_all_urls: Option<bool>,
+  /// Which `srcset` candidate becomes the `img` `src`: `"largest"` (default),
+  /// `"smallest"` or `"closest"` to `srcset_target_width`. `"keep"` leaves
+  /// `src` alone and absolutizes every candidate instead.
+  pub srcset_policy: Option<String>,
+  pub srcset_target_width: Option<u32>,
+}
+
+const URL_ATTRIBUTES: [(&str, &str); 16] = [
+  ("source[src]", "src"),
+  ("video[src]", "src"),
+  ("audio[src]", "src"),
+  ("track[src]", "src"),
+  ("embed[src]", "src"),
+  ("iframe[src]", "src"),
+  ("input[src]", "src"),
+  ("video[poster]", "poster"),
+  ("object[data]", "data"),
+  ("link[href]", "href"),
+  ("area[href]", "href"),
+  ("form[action]", "action"),
+  ("button[formaction]", "formaction"),
+  ("blockquote[cite]", "cite"),
+  ("q[cite]", "cite"),
+  ("img[data-src]", "data-src"),
+];
+
+static CSS_URL: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r#"url\(\s*(?:"([^"]*)"|'([^']*)'|([^"')]*?))\s*\)"#).unwrap());
+
+fn _absolutize_urls(
+  document: &NodeRef,
+  resolver: &UrlResolver,
+) -> Result<(), Box<dyn std::error::Error>> {
+  for (selector, attr) in URL_ATTRIBUTES {
+    let elements: Vec<_> = document
+      .select(selector)
+      .map_err(|_| format!("Failed to select {selector}"))?
+      .collect();
+    for element in elements {
+      let mut attributes = element.attributes.borrow_mut();
+      if let Some(new) = attributes.get(attr).and_then(|x| resolver.resolve(x).ok()) {
+        attributes.insert(attr, new);
+      }
+    }
+  }
+
+  let srcsets: Vec<_> = document
+    .select("img[srcset], source[srcset]")
+    .map_err(|_| "Failed to select srcsets")?
+    .collect();
+  for element in srcsets {
+    _absolutize_srcset(&mut element.attributes.borrow_mut(), resolver);
+  }
+
+  let styled: Vec<_> = document
+    .select("[style]")
+    .map_err(|_| "Failed to select inline styles")?
+    .collect();
+  for element in styled {
+    let mut attributes = element.attributes.borrow_mut();
+    let Some(style) = attributes.get("style") else {
+      continue;
+    };
+    let new = CSS_URL
+      .replace_all(style, |caps: &Captures| {
+        let (url, quote) = match (caps.get(1), caps.get(2), caps.get(3)) {
+          (Some(x), _, _) => (x.as_str(), "\""),
+          (_, Some(x), _) => (x.as_str(), "'"),
+          (_, _, x) => (x.map_or("", |x| x.as_str()), ""),
+        };
+        // `url(#id)` references an element of the document itself, e.g. an SVG filter
+        match resolver.resolve(url) {
+          Ok(resolved) if !url.is_empty() && !url.starts_with('#') => {
+            format!("url({quote}{resolved}{quote})")
+          }
+          _ => caps[0].to_string(),
+        }
+      })
+      .into_owned();
+    attributes.insert("style", new);
+  }
+
+  Ok(())
+}
+
+#[derive(Serialize)]
+#[napi(object)]
+pub struct HtmlRemoval {
+  /// Step that removed the nodes: `"cleanup"`, `"omce"`, `"exclude_tags"`,
+  /// `"main_content"` or `"readability"`.
+  pub stage: String,
+  /// Tag, selector or OMCE signature that matched. For `"readability"`, one of
+  /// `"unlikely"`, `"link_density"` or `"low_score"`.
+  pub rule: String,
+  pub nodes: u32,
+  /// Length of the removed text, with whitespace collapsed.
+  pub text_length: u32,
+}
+
+#[derive(Serialize)]
+#[napi(object)]
+pub struct TransformHtmlReport {
+  pub html: String,
+  pub removals: Vec<HtmlRemoval>,
+}
+
+/// Detaches nodes, tallying them per stage and rule when reporting is on.
+#[derive(Default)]
+struct RemovalLog(Option<Vec<HtmlRemoval>>);
+
+impl RemovalLog {
+  fn detach(&mut self, stage: &str, rule: &str, node: &NodeRef) {
+    let Some(removals) = self.0.as_mut() else {
+      node.detach();
+      return;
+    };
+    // Nodes inside an already-removed subtree were counted with it
+    let attached = node
+      .ancestors()
+      .last()
+      .is_some_and(|x| x.as_document().is_some());
+    if attached {
+      let text_length = _text_length(node).saturating_sub(1) as u32;
+      match removals
+        .iter_mut()
+        .find(|x| x.stage == stage && x.rule == rule)
+      {
+        Some(removal) => {
+          removal.nodes += 1;
+          removal.text_length += text_length;
+        }
+        None => removals.push(HtmlRemoval {
+          stage: stage.to_string(),
+          rule: rule.to_string(),
+          nodes: 1,
+          text_length,
+        }),
+      }
+    }
+    node.detach();
+  }
+}
+
+#[derive(PartialEq)]
+enum SrcsetPolicy {
+  Largest,
+  Smallest,
+  Closest(u32),
+  Keep,
+}
+
+/// Split a `srcset` into `(url, descriptor)` candidates the way browsers do,
+/// so commas inside URLs (e.g. `w_100,h_100` CDN transforms) don't split them.
+fn _parse_srcset(srcset: &str) -> Vec<(String, Option<String>)> {
+  let mut candidates = vec![];
+  let mut rest = srcset;
+  loop {
+    rest = rest.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == ',');
+    if rest.is_empty() {
+      break;
+    }
+
+    let url_end = rest
+      .find(|c: char| c.is_ascii_whitespace())
+      .unwrap_or(rest.len());
+    let (url, after) = rest.split_at(url_end);
+    rest = after;
+    if url.ends_with(',') {
+      candidates.push((url.trim_end_matches(',').to_string(), None));
+      continue;
+    }
+
+    // Descriptors run until the next comma outside parentheses
+    let mut depth = 0;
+    let descriptor_end = rest
+      .char_indices()
+      .find(|&(_, c)| {
+        match c {
+          '(' => depth += 1,
+          ')' if depth > 0 => depth -= 1,
+          ',' if depth == 0 => return true,
+          _ => {}
+        }
+        false
+      })
+      .map_or(rest.len(), |(i, _)| i);
+    let descriptor = rest[..descriptor_end].trim();
+    rest = &rest[descriptor_end..];
+
+    candidates.push((
+      url.to_string(),
+      (!descriptor.is_empty()).then(|| descriptor.to_string()),
+    ));
+  }
+  candidates
+}
+
+fn _absolutize_srcset(attributes: &mut kuchikiki::Attributes, resolver: &UrlResolver) {
+  let Some(srcset) = attributes.get("srcset") else {
+    return;
+  };
+  let new = _parse_srcset(srcset)
+    .into_iter()
+    .map(|(url, descriptor)| {
+      let url = resolver.resolve(&url).unwrap_or(url);
+      match descriptor {
+        Some(descriptor) => format!("{url} {descriptor}"),
+        None => url,
+      }
+    })
+    .collect::<Vec<_>>()
+    .join(", ");
+  attributes.insert("srcset", new);
+}
+
+struct ImageSource {
+  url: String,
+  size: f64,
+  is_x: bool,
+}
+
+fn _transform_html_inner(opts: TransformHtmlOptions) -> Result<String, Box<dyn std::error::Error>> {
+  _transform_html_logged(opts, &mut RemovalLog::default())
+}
+
+fn _transform_html_logged(
+  opts: TransformHtmlOptions,
+  removals: &mut RemovalLog,
+) -> Result<String, Box<dyn std::error::Error>> {
+  let mut document = parse_html().one(opts.html.as_ref());
+  let resolver = UrlResolver::new(&document, Url::parse(&opts.url)?)?;
+  let url = resolver.base_href.clone();
+
+  if !opts.include_tags.is_empty() {
+    let new_document = parse_html().one("<div></div>");
+    let root = new_document
+      .select_first("div")
+      .map_err(|_| "Failed to select root element")?;
+
+    for x in opts.include_tags.iter() {
+      let matching_nodes: Vec<_> = document
+        .select(x)
+        .map_err(|_| "Failed to include_tags tags")?
+        .collect();
+      for tag in matching_nodes {
+        root.as_node().append(tag.as_node().clone());
+      }
+    }
+
+    document = new_document;
+  }
+
+  for tag in ["head", "meta", "noscript", "style", "script"] {
+    while let Ok(x) = document.select_first(tag) {
+      removals.detach("cleanup", tag, x.as_node());
+    }
+  }
+
+  // OMCE first
+  if opts.only_main_content {
+    if let Some(signatures) = opts.omce_signatures.as_ref() {
+      let mut nodes_to_drop: Vec<(NodeRef, String)> = Vec::new();
+
+      let modes = signatures
+        .iter()
+        .map(|x| Into::<SignatureMode>::into(x.split(':').nth(1).unwrap().to_string()))
+        .collect::<HashSet<_>>();
+
+      for mode in modes {
+        let matcher = format!(":{}:", Into::<String>::into(mode.clone()));
+        let signatures = signatures
+          .iter()
+          .filter(|x| x.contains(&matcher))
+          .cloned()
+          .collect::<HashSet<_>>();
+
+        for edge in document.traverse() {
+          match edge {
+            NodeEdge::Start(_) => {}
+            NodeEdge::End(node) => {
+              if node.as_element().is_none() {
+                continue;
+              }
+              if node.text_contents().trim().is_empty() {
+                continue;
+              }
+
+              let signature = get_node_signature(&node, mode.clone());
+              if signatures.contains(&signature) {
+                nodes_to_drop.push((node, signature));
+              }
+            }
+          }
+        }
+      }
+
+      for (node, signature) in nodes_to_drop {
+        removals.detach("omce", &signature, &node);
+      }
+    }
+  }
+
+  for selector in opts.exclude_tags.iter() {
+    while let Ok(x) = document.select_first(selector) {
+      removals.detach("exclude_tags", selector, x.as_node());
+    }
+  }
+
+  let readability = match opts.main_content_mode.as_deref() {
+    None | Some("selectors") => false,
+    Some("readability") => true,
+    Some(mode) => return Err(format!("Unknown main content mode: {mode}").into()),
+  };
+
+  if opts.only_main_content && !(readability && _readability_main_content(&document, removals)?) {
+    let replace_defaults = opts.replace_default_selectors.unwrap_or(false);
+    let mut remove_selectors: Vec<&str> = if replace_defaults {
+      vec![]
+    } else {
+      EXCLUDE_NON_MAIN_TAGS.to_vec()
+    };
+    remove_selectors.extend(opts.remove_selectors.iter().flatten().map(|x| x.as_str()));
+    let mut force_include_selectors: Vec<&str> = if replace_defaults {
+      vec![]
+    } else {
+      FORCE_INCLUDE_MAIN_TAGS.to_vec()
+    };
+    force_include_selectors.extend(
+      opts
+        .force_include_selectors
+        .iter()
+        .flatten()
+        .map(|x| x.as_str()),
+    );
+
+    for selector in remove_selectors {
+      let x: Vec<_> = document
+        .select(selector)
+        .map_err(|_| format!("Failed to select tags: {selector}"))?
+        .collect();
+      for tag in x {
+        if !force_include_selectors.iter().any(|x| {
+          tag
+            .as_node()
+            .select(x)
+            .is_ok_and(|mut x| x.next().is_some())
+        }) {
+          removals.detach("main_content", selector, tag.as_node());
+        }
+      }
+    }
+  }
+
+  let srcset_policy = match opts.srcset_policy.as_deref() {
+    None | Some("largest") => SrcsetPolicy::Largest,
+    Some("smallest") => SrcsetPolicy::Smallest,
+    Some("closest") => SrcsetPolicy::Closest(
+      opts
+        .srcset_target_width
+        .ok_or("srcset_target_width is required for the closest srcset policy")?,
+    ),
+    Some("keep") => SrcsetPolicy::Keep,
+    Some(policy) => return Err(format!("Unknown srcset policy: {policy}").into()),
+  };
+
+  if srcset_policy == SrcsetPolicy::Keep {
+    let srcsets: Vec<_> = document
+      .select("img[srcset], source[srcset]")
+      .map_err(|_| "Failed to select srcsets")?
+      .collect();
+    for element in srcsets {
+      _absolutize_srcset(&mut element.attributes.borrow_mut(), &resolver);
+    }
+  }
+
+  let srcset_images: Vec<_> = match srcset_policy {
+    SrcsetPolicy::Keep => vec![],
+    _ => document
+      .select("img[srcset]")
+      .map_err(|_| "Failed to select srcset images")?
+      .collect(),
+  };
+  for img in srcset_images {
+    let mut sizes: Vec<ImageSource> = _parse_srcset(
+      img
+        .attributes
+        .borrow()
+        .get("srcset")
+        .ok_or("Failed to get srcset")?,
+    )
+    .into_iter()
+    .filter_map(|(url, descriptor)| {
+      let descriptor = descriptor.unwrap_or_else(|| "1x".to_string());
+      let is_x = descriptor.ends_with('x');
+      if !is_x && !descriptor.ends_with('w') {
+        return None;
+      }
+      let size = descriptor[..descriptor.len() - 1].parse().ok()?;
+      Some(ImageSource { url, size, is_x })
+    })
+    .collect();
 
     if sizes.iter().all(|x| x.is_x) {
       if let Some(src) = img.attributes.borrow().get("src").map(|x| x.to_string()) {
@@ -519,11 +1569,26 @@ fn _transform_html_inner(opts: TransformHtmlOptions) -> Result<String, Box<dyn s
         .unwrap_or(std::cmp::Ordering::Equal)
     });
 
-    if let Some(biggest) = sizes.first() {
+    let chosen = match srcset_policy {
+      SrcsetPolicy::Smallest => sizes.last(),
+      // Widths only; densities can't be compared to a width without layout
+      SrcsetPolicy::Closest(width) => sizes
+        .iter()
+        .filter(|x| !x.is_x)
+        .min_by(|a, b| {
+          (a.size - width as f64)
+            .abs()
+            .total_cmp(&(b.size - width as f64).abs())
+        })
+        .or(sizes.first()),
+      _ => sizes.first(),
+    };
+
+    if let Some(chosen) = chosen {
       img
         .attributes
         .borrow_mut()
-        .insert("src", biggest.url.clone());
+        .insert("src", chosen.url.clone());
     }
   }
 
@@ -562,6 +1627,10 @@ fn _transform_html_inner(opts: TransformHtmlOptions) -> Result<String, Box<dyn s
     }
   }
 
+  if opts.absolutize_all_urls.unwrap_or(false) {
+    _absolutize_urls(&document, &resolver)?;
+  }
+
   Ok(document.to_string())
 }
 
@@ -571,6 +1640,915 @@ pub fn transform_html(opts: TransformHtmlOptions) -> napi::Result<String> {
   _transform_html_inner(opts).map_err(to_napi_err)
 }
 
+/// Like `transform_html`, but also reports what each cleanup step removed.
+#[napi]
+pub fn transform_html_with_report(opts: TransformHtmlOptions) -> napi::Result<TransformHtmlReport> {
+  let mut removals = RemovalLog(Some(vec![]));
+  let html = _transform_html_logged(opts, &mut removals).map_err(to_napi_err)?;
+  Ok(TransformHtmlReport {
+    html,
+    removals: removals.0.unwrap_or_default(),
+  })
+}
+
+#[derive(Deserialize, Serialize)]
+#[napi(object)]
+pub struct HtmlTransformStreamOptions {
+  pub url: String,
+  #[serde(default)]
+  pub exclude_tags: Vec<String>,
+}
+
+const STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+type StreamRewriter = HtmlRewriter<'static, Box<dyn FnMut(&[u8])>>;
+
+/// Streaming subset of `transform_html`: removes head, meta, noscript, style
+/// and script, applies `exclude_tags` and absolutizes `img[src]`/`a[href]` in
+/// one pass without building a DOM. Output is returned as soon as it is
+/// rewritten, so it may split UTF-8 sequences across chunks.
+#[napi]
+pub struct HtmlTransformStream {
+  rewriter: Option<StreamRewriter>,
+  output: Rc<RefCell<Vec<u8>>>,
+}
+
+impl HtmlTransformStream {
+  fn compile(opts: HtmlTransformStreamOptions) -> Result<Self, Box<dyn std::error::Error>> {
+    let url = Url::parse(&opts.url)?;
+    let base = Rc::new(RefCell::new(url.clone()));
+    let base_seen = Cell::new(false);
+
+    let absolutize = |attr: &'static str| {
+      let base = base.clone();
+      move |el: &mut Element| {
+        if let Some(old) = el.get_attribute(attr) {
+          if let Ok(new) = base.borrow().join(&old) {
+            el.set_attribute(attr, new.as_str())?;
+          }
+        }
+        Ok(())
+      }
+    };
+
+    let mut handlers = vec![
+      // Only a <base> seen before a link applies to it, unlike in `transform_html`
+      element!("base[href]", {
+        let base = base.clone();
+        move |el| {
+          if !base_seen.replace(true) {
+            if let Some(new) = el.get_attribute("href").and_then(|x| url.join(&x).ok()) {
+              *base.borrow_mut() = new;
+            }
+          }
+          Ok(())
+        }
+      }),
+      element!("head, meta, noscript, style, script", |el| {
+        el.remove();
+        Ok(())
+      }),
+      element!("img[src]", absolutize("src")),
+      element!("a[href]", absolutize("href")),
+    ];
+    for x in opts.exclude_tags.iter() {
+      let selector: Selector = x
+        .parse()
+        .map_err(|e| format!("Failed to parse exclude_tags selector {x}: {e}"))?;
+      handlers.push((
+        Cow::Owned(selector),
+        ElementContentHandlers::default().element(|el: &mut Element| {
+          el.remove();
+          Ok(())
+        }),
+      ));
+    }
+
+    let output = Rc::new(RefCell::new(vec![]));
+    let sink = output.clone();
+    let rewriter = HtmlRewriter::new(
+      Settings {
+        element_content_handlers: handlers,
+        ..Settings::new()
+      },
+      Box::new(move |chunk: &[u8]| sink.borrow_mut().extend_from_slice(chunk))
+        as Box<dyn FnMut(&[u8])>,
+    );
+
+    Ok(HtmlTransformStream {
+      rewriter: Some(rewriter),
+      output,
+    })
+  }
+
+  fn _write(&mut self, chunk: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    // lol_html panics on writes after an error, so a failed rewriter is dropped
+    let mut rewriter = self.rewriter.take().ok_or("Stream has already ended")?;
+    rewriter.write(chunk)?;
+    self.rewriter = Some(rewriter);
+    Ok(self.output.take())
+  }
+
+  fn _end(&mut self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let rewriter = self.rewriter.take().ok_or("Stream has already ended")?;
+    rewriter.end()?;
+    Ok(self.output.take())
+  }
+}
+
+#[napi]
+impl HtmlTransformStream {
+  #[napi(constructor)]
+  pub fn new(opts: HtmlTransformStreamOptions) -> napi::Result<Self> {
+    HtmlTransformStream::compile(opts).map_err(to_napi_err)
+  }
+
+  /// Rewrite `chunk` and return the output that is ready so far. Input that
+  /// may still be part of an unfinished tag is held back until later chunks.
+  #[napi]
+  pub fn write(&mut self, chunk: Buffer) -> napi::Result<Buffer> {
+    self._write(&chunk).map(Buffer::from).map_err(to_napi_err)
+  }
+
+  /// Finish the stream and return the remaining output.
+  #[napi]
+  pub fn end(&mut self) -> napi::Result<Buffer> {
+    self._end().map(Buffer::from).map_err(to_napi_err)
+  }
+}
+
+#[derive(Deserialize, Serialize)]
+#[napi(object)]
+pub struct TransformHtmlStreamOptions {
+  pub html: String,
+  pub url: String,
+  #[serde(default)]
+  pub exclude_tags: Vec<String>,
+}
+
+fn _transform_html_stream_inner(
+  opts: TransformHtmlStreamOptions,
+) -> Result<String, Box<dyn std::error::Error>> {
+  let mut stream = HtmlTransformStream::compile(HtmlTransformStreamOptions {
+    url: opts.url,
+    exclude_tags: opts.exclude_tags,
+  })?;
+
+  let mut output = Vec::with_capacity(opts.html.len());
+  for chunk in opts.html.as_bytes().chunks(STREAM_CHUNK_SIZE) {
+    output.extend(stream._write(chunk)?);
+  }
+  output.extend(stream._end()?);
+
+  Ok(String::from_utf8(output)?)
+}
+
+/// Like `HtmlTransformStream`, but for a whole document at once.
+#[napi]
+pub fn transform_html_stream(opts: TransformHtmlStreamOptions) -> napi::Result<String> {
+  _transform_html_stream_inner(opts).map_err(to_napi_err)
+}
+
+const MARKDOWN_SKIP_TAGS: [&str; 8] = [
+  "head", "script", "style", "noscript", "template", "meta", "link", "title",
+];
+
+const MARKDOWN_MAX_DEPTH: usize = 256;
+
+const MARKDOWN_BLOCK_TAGS: [&str; 23] = [
+  "address",
+  "article",
+  "aside",
+  "body",
+  "dd",
+  "details",
+  "dialog",
+  "div",
+  "dl",
+  "dt",
+  "fieldset",
+  "figcaption",
+  "figure",
+  "footer",
+  "form",
+  "header",
+  "hgroup",
+  "html",
+  "main",
+  "nav",
+  "p",
+  "section",
+  "summary",
+];
+
+struct MarkdownWriter {
+  out: String,
+  tight: bool,
+}
+
+impl MarkdownWriter {
+  fn new(tight: bool) -> Self {
+    MarkdownWriter {
+      out: String::new(),
+      tight,
+    }
+  }
+
+  /// Writes literal text, collapsing whitespace and escaping characters that
+  /// would otherwise be read as Markdown.
+  fn text(&mut self, text: &str) {
+    let mut escape_at = None;
+    for (i, c) in text.char_indices() {
+      if c.is_whitespace() {
+        if !self.out.is_empty() && !self.out.ends_with([' ', '\n']) {
+          self.out.push(' ');
+        }
+        continue;
+      }
+
+      if self.out.is_empty() || self.out.ends_with('\n') {
+        escape_at = _markdown_block_marker(&text[i..]).map(|x| i + x);
+      }
+      if escape_at == Some(i) || matches!(c, '\\' | '*' | '_' | '`' | '[' | ']') {
+        self.out.push('\\');
+      }
+      self.out.push(c);
+    }
+  }
+
+  fn raw(&mut self, text: &str) {
+    self.out.push_str(text);
+  }
+
+  fn trim_trailing_spaces(&mut self) {
+    let trimmed = self.out.trim_end_matches([' ', '\t']).len();
+    self.out.truncate(trimmed);
+  }
+
+  fn line_break(&mut self) {
+    self.trim_trailing_spaces();
+    if !self.out.is_empty() && !self.out.ends_with('\n') {
+      self.out.push('\n');
+    }
+  }
+
+  fn block_break(&mut self) {
+    if self.tight {
+      return self.line_break();
+    }
+
+    self.trim_trailing_spaces();
+    if self.out.is_empty() {
+      return;
+    }
+    while !self.out.ends_with("\n\n") {
+      self.out.push('\n');
+    }
+  }
+
+  /// Writes `inner` wrapped in `open`/`close`, keeping the surrounding
+  /// whitespace outside of the markers so the emphasis stays valid.
+  fn wrap_inline(&mut self, inner: &str, open: &str, close: &str) {
+    let trimmed = inner.trim();
+    if trimmed.is_empty() {
+      self.text(inner);
+      return;
+    }
+
+    if inner.starts_with(char::is_whitespace) {
+      self.text(" ");
+    }
+    self.raw(open);
+    self.raw(trimmed);
+    self.raw(close);
+    if inner.ends_with(char::is_whitespace) {
+      self.text(" ");
+    }
+  }
+
+  fn finish(self) -> String {
+    let mut out = String::with_capacity(self.out.len());
+    let mut newlines = 0;
+    for line in self.out.split('\n') {
+      if line.trim().is_empty() {
+        newlines += 1;
+        continue;
+      }
+      if !out.is_empty() {
+        out.push_str(if newlines > 1 { "\n\n" } else { "\n" });
+      }
+      out.push_str(line.trim_end_matches([' ', '\t']));
+      if line.ends_with("  ") {
+        out.push_str("  ");
+      }
+      newlines = 1;
+    }
+    out
+  }
+}
+
+/// Offset in `line` of the character to backslash-escape so the start of a
+/// text line isn't read as a heading, quote, list item or rule.
+fn _markdown_block_marker(line: &str) -> Option<usize> {
+  let line = line.trim_end();
+  // Markers only count when followed by whitespace, e.g. "- a" but not "-5"
+  let ends_marker = |len: usize| line[len..].chars().next().is_none_or(char::is_whitespace);
+
+  let digits = line.bytes().take_while(u8::is_ascii_digit).count();
+  if digits > 0 {
+    let is_marker = line[digits..].starts_with(['.', ')']) && ends_marker(digits + 1);
+    return is_marker.then_some(digits);
+  }
+
+  let hashes = line.bytes().take_while(|&c| c == b'#').count();
+  let is_marker = match line.bytes().next() {
+    Some(b'>') => true,
+    Some(b'#') => hashes <= 6 && ends_marker(hashes),
+    Some(b'-' | b'+') => ends_marker(1),
+    _ => false,
+  };
+  // A whole line of `=` or `-` underlines the previous one as a heading
+  let is_underline = |marker: u8| line.bytes().all(|c| c == marker);
+  (is_marker || (!line.is_empty() && (is_underline(b'=') || is_underline(b'-')))).then_some(0)
+}
+
+fn _markdown_escape_cell(cell: &str) -> String {
+  cell
+    .split_whitespace()
+    .collect::<Vec<_>>()
+    .join(" ")
+    .replace('|', "\\|")
+}
+
+fn _markdown_code_fence(content: &str, marker: char, min: usize) -> String {
+  let mut longest = 0;
+  let mut current = 0;
+  for c in content.chars() {
+    if c == marker {
+      current += 1;
+      longest = longest.max(current);
+    } else {
+      current = 0;
+    }
+  }
+
+  marker.to_string().repeat(min.max(longest + 1))
+}
+
+fn _markdown_code_language(node: &NodeRef) -> Option<String> {
+  let classes = node
+    .as_element()?
+    .attributes
+    .borrow()
+    .get("class")?
+    .to_string();
+  classes.split_whitespace().find_map(|class| {
+    class
+      .strip_prefix("language-")
+      .or_else(|| class.strip_prefix("lang-"))
+      .filter(|x| !x.is_empty())
+      .map(|x| x.to_string())
+  })
+}
+
+fn _markdown_resolve(base: Option<&Url>, href: &str) -> String {
+  match base.map(|base| base.join(href)) {
+    Some(Ok(resolved)) => resolved.to_string(),
+    _ => href.to_string(),
+  }
+}
+
+fn _markdown_title(node: &NodeRef) -> String {
+  node
+    .as_element()
+    .and_then(|x| {
+      x.attributes
+        .borrow()
+        .get("title")
+        .map(|x| x.trim().to_string())
+    })
+    .filter(|x| !x.is_empty())
+    .map(|x| format!(" \"{}\"", x.replace('"', "\\\"")))
+    .unwrap_or_default()
+}
+
+fn _markdown_children(node: &NodeRef, base: Option<&Url>, tight: bool, depth: usize) -> String {
+  let mut writer = MarkdownWriter::new(tight);
+  for child in node.children() {
+    _markdown_node(&child, base, &mut writer, depth);
+  }
+  writer.out
+}
+
+fn _markdown_list(
+  node: &NodeRef,
+  base: Option<&Url>,
+  ordered: bool,
+  writer: &mut MarkdownWriter,
+  depth: usize,
+) {
+  let mut index = node
+    .as_element()
+    .and_then(|x| {
+      x.attributes
+        .borrow()
+        .get("start")
+        .and_then(|x| x.parse::<i64>().ok())
+    })
+    .unwrap_or(1);
+
+  writer.block_break();
+  for item in node.children() {
+    let is_item = item.as_element().is_some_and(|x| &*x.name.local == "li");
+    if !is_item {
+      continue;
+    }
+
+    let prefix = if ordered {
+      format!("{index}. ")
+    } else {
+      "- ".to_string()
+    };
+    index += 1;
+
+    let content = MarkdownWriter {
+      out: _markdown_children(&item, base, true, depth + 1),
+      tight: true,
+    }
+    .finish();
+    let indent = " ".repeat(prefix.len());
+
+    writer.line_break();
+    writer.raw(&prefix);
+    for (i, line) in content.lines().enumerate() {
+      if i > 0 {
+        writer.raw("\n");
+        if !line.is_empty() {
+          writer.raw(&indent);
+        }
+      }
+      writer.raw(line);
+    }
+  }
+  writer.block_break();
+}
+
+fn _markdown_table(node: &NodeRef, base: Option<&Url>, writer: &mut MarkdownWriter, depth: usize) {
+  let mut rows: Vec<Vec<String>> = Vec::new();
+  let mut has_header = false;
+
+  for edge in node.traverse() {
+    let NodeEdge::Start(row) = edge else {
+      continue;
+    };
+    let is_row = row.as_element().is_some_and(|x| &*x.name.local == "tr");
+    if !is_row {
+      continue;
+    }
+    // Only direct rows of this table, not rows of nested tables.
+    let owner = row
+      .ancestors()
+      .find(|x| x.as_element().is_some_and(|x| &*x.name.local == "table"));
+    if owner.as_ref() != Some(node) {
+      continue;
+    }
+
+    let mut cells = Vec::new();
+    for cell in row.children() {
+      let Some(name) = cell.as_element().map(|x| x.name.local.to_string()) else {
+        continue;
+      };
+      if name != "td" && name != "th" {
+        continue;
+      }
+      if rows.is_empty() && name == "th" {
+        has_header = true;
+      }
+      let content = MarkdownWriter {
+        out: _markdown_children(&cell, base, true, depth + 1),
+        tight: true,
+      }
+      .finish();
+      cells.push(_markdown_escape_cell(&content));
+    }
+    rows.push(cells);
+  }
+
+  let columns = rows.iter().map(|x| x.len()).max().unwrap_or(0);
+  if columns == 0 {
+    return;
+  }
+
+  if !has_header {
+    rows.insert(0, vec![String::new(); columns]);
+  }
+
+  writer.block_break();
+  for (i, row) in rows.iter().enumerate() {
+    let mut line = String::from("|");
+    for column in 0..columns {
+      line.push(' ');
+      line.push_str(row.get(column).map(String::as_str).unwrap_or(""));
+      line.push_str(" |");
+    }
+    writer.line_break();
+    writer.raw(&line);
+
+    if i == 0 {
+      writer.raw("\n|");
+      writer.raw(&" --- |".repeat(columns));
+    }
+  }
+  writer.block_break();
+}
+
+fn _markdown_node(node: &NodeRef, base: Option<&Url>, writer: &mut MarkdownWriter, depth: usize) {
+  if let Some(text) = node.as_text() {
+    writer.text(&text.borrow());
+    return;
+  }
+
+  let Some(element) = node.as_element() else {
+    for child in node.children() {
+      _markdown_node(&child, base, writer, depth + 1);
+    }
+    return;
+  };
+
+  let name = &*element.name.local;
+
+  if MARKDOWN_SKIP_TAGS.contains(&name) {
+    return;
+  }
+
+  // Deeper content is written as plain text so nesting can't overflow the stack
+  if depth > MARKDOWN_MAX_DEPTH {
+    writer.text(&node.text_contents());
+    return;
+  }
+
+  match name {
+    "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+      let level = name[1..].parse::<usize>().unwrap_or(1);
+      let content = _markdown_children(node, base, true, depth + 1);
+      let content = content.split_whitespace().collect::<Vec<_>>().join(" ");
+      if !content.is_empty() {
+        writer.block_break();
+        writer.raw(&"#".repeat(level));
+        writer.raw(" ");
+        writer.raw(&content);
+        writer.block_break();
+      }
+    }
+    "br" => {
+      writer.trim_trailing_spaces();
+      writer.raw("  \n");
+    }
+    "hr" => {
+      writer.block_break();
+      writer.raw("---");
+      writer.block_break();
+    }
+    "strong" | "b" => {
+      let content = _markdown_children(node, base, true, depth + 1);
+      writer.wrap_inline(&content, "**", "**");
+    }
+    "em" | "i" => {
+      let content = _markdown_children(node, base, true, depth + 1);
+      writer.wrap_inline(&content, "*", "*");
+    }
+    "del" | "s" | "strike" => {
+      let content = _markdown_children(node, base, true, depth + 1);
+      writer.wrap_inline(&content, "~~", "~~");
+    }
+    "code" | "kbd" | "samp" | "tt" => {
+      let content = node.text_contents();
+      let fence = _markdown_code_fence(&content, '`', 1);
+      let padding = if content.starts_with('`') || content.ends_with('`') {
+        " "
+      } else {
+        ""
+      };
+      if !content.is_empty() {
+        writer.raw(&format!("{fence}{padding}{content}{padding}{fence}"));
+      }
+    }
+    "pre" => {
+      let content = node.text_contents();
+      let content = content.trim_end_matches('\n');
+      let language = _markdown_code_language(node)
+        .or_else(|| {
+          node
+            .select_first("code")
+            .ok()
+            .and_then(|code| _markdown_code_language(code.as_node()))
+        })
+        .unwrap_or_default();
+      let fence = _markdown_code_fence(content, '`', 3);
+
+      writer.block_break();
+      writer.raw(&format!("{fence}{language}\n{content}\n{fence}"));
+      writer.block_break();
+    }
+    "a" => {
+      let content = _markdown_children(node, base, true, depth + 1);
+      let href = element
+        .attributes
+        .borrow()
+        .get("href")
+        .map(|x| x.trim().to_string());
+
+      match href {
+        Some(href) if !href.is_empty() && !content.trim().is_empty() => {
+          let content = content.split_whitespace().collect::<Vec<_>>().join(" ");
+          let href = _markdown_resolve(base, &href).replace(' ', "%20");
+          let title = _markdown_title(node);
+          writer.wrap_inline(&content, "[", &format!("]({href}{title})"));
+        }
+        _ => writer.text(&content),
+      }
+    }
+    "img" => {
+      let attrs = element.attributes.borrow();
+      if let Some(src) = attrs.get("src").map(|x| x.trim()).filter(|x| !x.is_empty()) {
+        let alt = attrs
+          .get("alt")
+          .map(|x| x.split_whitespace().collect::<Vec<_>>().join(" "))
+          .unwrap_or_default();
+        let src = _markdown_resolve(base, src).replace(' ', "%20");
+        let title = _markdown_title(node);
+        writer.raw(&format!("![{alt}]({src}{title})"));
+      }
+    }
+    "ul" | "menu" => _markdown_list(node, base, false, writer, depth),
+    "ol" => _markdown_list(node, base, true, writer, depth),
+    "li" => {
+      // Stray list items outside of a list are rendered as bullets.
+      writer.line_break();
+      writer.raw("- ");
+      for child in node.children() {
+        _markdown_node(&child, base, writer, depth + 1);
+      }
+      writer.line_break();
+    }
+    "blockquote" => {
+      let content = MarkdownWriter {
+        out: _markdown_children(node, base, false, depth + 1),
+        tight: false,
+      }
+      .finish();
+      if !content.is_empty() {
+        writer.block_break();
+        let quoted = content
+          .lines()
+          .map(|line| {
+            if line.is_empty() {
+              ">".to_string()
+            } else {
+              format!("> {line}")
+            }
+          })
+          .collect::<Vec<_>>()
+          .join("\n");
+        writer.raw(&quoted);
+        writer.block_break();
+      }
+    }
+    "table" => _markdown_table(node, base, writer, depth),
+    _ if MARKDOWN_BLOCK_TAGS.contains(&name) => {
+      writer.block_break();
+      for child in node.children() {
+        _markdown_node(&child, base, writer, depth + 1);
+      }
+      writer.block_break();
+    }
+    _ => {
+      for child in node.children() {
+        _markdown_node(&child, base, writer, depth + 1);
+      }
+    }
+  }
+}
+
+fn _html_to_markdown(html: &str, url: Option<&str>) -> Result<String, Box<dyn std::error::Error>> {
+  let document = parse_html().one(html);
+  let base = match url {
+    Some(url) => Some(Url::parse(&_extract_base_href_from_document(
+      &document,
+      &Url::parse(url)?,
+    )?)?),
+    None => None,
+  };
+
+  let mut writer = MarkdownWriter::new(false);
+  _markdown_node(&document, base.as_ref(), &mut writer, 0);
+  Ok(writer.finish())
+}
+
+/// Convert HTML content into Markdown, resolving relative links against the
+/// document's base href when a URL is provided.
+#[napi]
+pub fn html_to_markdown(html: String, url: Option<String>) -> napi::Result<String> {
+  _html_to_markdown(&html, url.as_deref()).map_err(to_napi_err)
+}
+
+const FINGERPRINT_SHINGLE_SIZE: u32 = 3;
+const FINGERPRINT_MINHASH_SIZE: u32 = 64;
+const FINGERPRINT_MAX_MINHASH_SIZE: u32 = 1024;
+
+#[derive(Deserialize, Serialize, Default)]
+#[napi(object)]
+pub struct ContentFingerprintOptions {
+  /// Page URL, used to run HTML input through `transform_html`.
+  pub url: Option<String>,
+  /// Fingerprint only the main content of HTML input. Defaults to true.
+  pub only_main_content: Option<bool>,
+  pub omce_signatures: Option<Vec<String>>,
+  /// Words per shingle. Defaults to 3.
+  pub shingle_size: Option<u32>,
+  /// Number of MinHash values, between 1 and 1024. Defaults to 64.
+  pub minhash_size: Option<u32>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[napi(object)]
+pub struct ContentFingerprint {
+  /// 64-bit SimHash as 16 hex digits.
+  pub simhash: String,
+  pub minhash: Vec<u32>,
+  pub token_count: u32,
+}
+
+#[derive(Deserialize, Serialize)]
+#[napi(object)]
+pub struct ContentSimilarity {
+  /// Number of differing SimHash bits (0-64).
+  pub simhash_distance: u32,
+  /// `1 - simhash_distance / 64`.
+  pub simhash_similarity: f64,
+  /// MinHash estimate of the Jaccard similarity of the shingle sets.
+  pub jaccard: f64,
+}
+
+/// 64-bit FNV-1a over `seed` and the space-separated tokens, finished with
+/// the MurmurHash3 mixer so every bit is usable. Fingerprints are stored and
+/// compared across processes, so this must not depend on the Rust version.
+fn _fingerprint_hash(tokens: &[&str], seed: u64) -> u64 {
+  const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+  const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+  let separated = tokens
+    .iter()
+    .enumerate()
+    .flat_map(|(i, token)| (i > 0).then_some(b' ').into_iter().chain(token.bytes()));
+  let mut hash = seed
+    .to_le_bytes()
+    .into_iter()
+    .chain(separated)
+    .fold(FNV_OFFSET_BASIS, |hash, byte| {
+      (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+    });
+
+  hash ^= hash >> 33;
+  hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+  hash ^= hash >> 33;
+  hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+  hash ^ (hash >> 33)
+}
+
+fn _fingerprint_text(
+  html_or_text: &str,
+  opts: &ContentFingerprintOptions,
+) -> Result<String, Box<dyn std::error::Error>> {
+  if !html_or_text.trim_start().starts_with('<') {
+    return Ok(html_or_text.to_string());
+  }
+
+  let html = _transform_html_inner(TransformHtmlOptions {
+    html: html_or_text.to_string(),
+    url: opts
+      .url
+      .clone()
+      .unwrap_or_else(|| "http://localhost/".to_string()),
+    include_tags: vec![],
+    exclude_tags: vec![],
+    only_main_content: opts.only_main_content.unwrap_or(true),
+    omce_signatures: opts.omce_signatures.clone(),
+    main_content_mode: None,
+    remove_selectors: None,
+    force_include_selectors: None,
+    replace_default_selectors: None,
+    absolutize_all_urls: None,
+    srcset_policy: None,
+    srcset_target_width: None,
+  })?;
+
+  Ok(parse_html().one(html).text_contents())
+}
+
+fn _content_fingerprint(
+  html_or_text: &str,
+  opts: &ContentFingerprintOptions,
+) -> Result<ContentFingerprint, Box<dyn std::error::Error>> {
+  let text = _fingerprint_text(html_or_text, opts)?.to_lowercase();
+  let tokens: Vec<&str> = text
+    .split(|c: char| !c.is_alphanumeric())
+    .filter(|x| !x.is_empty())
+    .collect();
+
+  let shingle_size = opts.shingle_size.unwrap_or(FINGERPRINT_SHINGLE_SIZE).max(1) as usize;
+  let shingles: HashSet<&[&str]> = if tokens.len() <= shingle_size {
+    std::iter::once(&tokens[..])
+      .filter(|x| !x.is_empty())
+      .collect()
+  } else {
+    tokens.windows(shingle_size).collect()
+  };
+
+  let mut weights = [0i64; 64];
+  let minhash_size = opts
+    .minhash_size
+    .unwrap_or(FINGERPRINT_MINHASH_SIZE)
+    .clamp(1, FINGERPRINT_MAX_MINHASH_SIZE);
+  let mut minhash = vec![u32::MAX; minhash_size as usize];
+
+  for shingle in &shingles {
+    let hash = _fingerprint_hash(shingle, 0);
+    for (bit, weight) in weights.iter_mut().enumerate() {
+      *weight += if hash & (1 << bit) != 0 { 1 } else { -1 };
+    }
+
+    let h2 = _fingerprint_hash(shingle, 1) | 1;
+    for (i, min) in minhash.iter_mut().enumerate() {
+      let value = (hash.wrapping_add((i as u64).wrapping_mul(h2)) >> 32) as u32;
+      *min = (*min).min(value);
+    }
+  }
+
+  let simhash = weights
+    .iter()
+    .enumerate()
+    .filter(|(_, weight)| **weight > 0)
+    .fold(0u64, |acc, (bit, _)| acc | (1 << bit));
+
+  Ok(ContentFingerprint {
+    simhash: format!("{simhash:016x}"),
+    minhash,
+    token_count: tokens.len() as u32,
+  })
+}
+
+/// Compute SimHash and MinHash fingerprints of a page's text for
+/// near-duplicate detection. HTML input is cleaned with `transform_html`
+/// first; anything else is treated as plain text.
+#[napi]
+pub fn content_fingerprint(
+  html_or_text: String,
+  options: Option<ContentFingerprintOptions>,
+) -> napi::Result<ContentFingerprint> {
+  _content_fingerprint(&html_or_text, &options.unwrap_or_default()).map_err(to_napi_err)
+}
+
+fn _content_similarity(
+  a: &ContentFingerprint,
+  b: &ContentFingerprint,
+) -> Result<ContentSimilarity, Box<dyn std::error::Error>> {
+  let a_simhash = u64::from_str_radix(&a.simhash, 16)?;
+  let b_simhash = u64::from_str_radix(&b.simhash, 16)?;
+  if a.minhash.len() != b.minhash.len() {
+    return Err("Fingerprints have different MinHash sizes".into());
+  }
+
+  let simhash_distance = (a_simhash ^ b_simhash).count_ones();
+  let jaccard = if a.token_count == 0 && b.token_count == 0 {
+    1.0
+  } else if a.minhash.is_empty() {
+    0.0
+  } else {
+    a.minhash
+      .iter()
+      .zip(&b.minhash)
+      .filter(|(x, y)| x == y)
+      .count() as f64
+      / a.minhash.len() as f64
+  };
+
+  Ok(ContentSimilarity {
+    simhash_distance,
+    simhash_similarity: 1.0 - simhash_distance as f64 / 64.0,
+    jaccard,
+  })
+}
+
+/// Compare two fingerprints from `content_fingerprint`.
+#[napi]
+pub fn content_similarity(
+  a: ContentFingerprint,
+  b: ContentFingerprint,
+) -> napi::Result<ContentSimilarity> {
+  _content_similarity(&a, &b).map_err(to_napi_err)
+}
+
 fn _get_inner_json(html: &str) -> Result<String, ()> {
   Ok(parse_html().one(html).select_first("body")?.text_contents())
 }
@@ -659,14 +2637,24 @@ pub fn extract_attributes(
   _extract_attributes(&html, &options).map_err(to_napi_err)
 }
 
-fn _extract_images(html: &str, base_url: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
-  let document = parse_html().one(html);
-  let base_url = Url::parse(base_url)?;
-  let base_href = _extract_base_href_from_document(&document, &base_url)?;
-  let base_href_url = Url::parse(&base_href)?;
-  let mut images = HashSet::<String>::new();
+/// Resolves URLs found in a document: protocol-relative URLs against the
+/// page URL, relative ones against the `<base href>`, and `data:`/`blob:`
+/// and absolute http(s) URLs as-is.
+struct UrlResolver {
+  page_url: Url,
+  base_href: Url,
+}
+
+impl UrlResolver {
+  fn new(document: &NodeRef, page_url: Url) -> Result<Self, Box<dyn std::error::Error>> {
+    let base_href = Url::parse(&_extract_base_href_from_document(document, &page_url)?)?;
+    Ok(UrlResolver {
+      page_url,
+      base_href,
+    })
+  }
 
-  let resolve_image_url = |src: &str| -> Result<String, Box<dyn std::error::Error>> {
+  fn resolve(&self, src: &str) -> Result<String, url::ParseError> {
     if src.starts_with("data:") || src.starts_with("blob:") {
       return Ok(src.to_string());
     }
@@ -674,12 +2662,16 @@ fn _extract_images(html: &str, base_url: &str) -> Result<Vec<String>, Box<dyn st
       return Ok(src.to_string());
     }
     if src.starts_with("//") {
-      let resolved = base_url.join(src)?;
-      return Ok(resolved.to_string());
+      return Ok(self.page_url.join(src)?.to_string());
     }
-    let resolved = base_href_url.join(src)?;
-    Ok(resolved.to_string())
-  };
+    Ok(self.base_href.join(src)?.to_string())
+  }
+}
+
+fn _extract_images(html: &str, base_url: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+  let document = parse_html().one(html);
+  let resolver = UrlResolver::new(&document, Url::parse(base_url)?)?;
+  let mut images = HashSet::<String>::new();
 
   // <img>
   let img_elements: Vec<_> = match document
@@ -694,25 +2686,21 @@ fn _extract_images(html: &str, base_url: &str) -> Result<Vec<String>, Box<dyn st
     let attrs = img.attributes.borrow();
 
     if let Some(src) = attrs.get("src") {
-      if let Ok(resolved) = resolve_image_url(src) {
+      if let Ok(resolved) = resolver.resolve(src) {
         images.insert(resolved);
       }
     }
 
     if let Some(data_src) = attrs.get("data-src") {
-      if let Ok(resolved) = resolve_image_url(data_src) {
+      if let Ok(resolved) = resolver.resolve(data_src) {
         images.insert(resolved);
       }
     }
 
     if let Some(srcset) = attrs.get("srcset") {
-      for part in srcset.split(',') {
-        if let Some(url) = part.trim().split_whitespace().next() {
-          if !url.is_empty() {
-            if let Ok(resolved) = resolve_image_url(url) {
-              images.insert(resolved);
-            }
-          }
+      for (url, _) in _parse_srcset(srcset) {
+        if let Ok(resolved) = resolver.resolve(&url) {
+          images.insert(resolved);
         }
       }
     }
@@ -729,13 +2717,9 @@ fn _extract_images(html: &str, base_url: &str) -> Result<Vec<String>, Box<dyn st
 
   for source in source_elements {
     if let Some(srcset) = source.attributes.borrow().get("srcset") {
-      for part in srcset.split(',') {
-        if let Some(url) = part.trim().split_whitespace().next() {
-          if !url.is_empty() {
-            if let Ok(resolved) = resolve_image_url(url) {
-              images.insert(resolved);
-            }
-          }
+      for (url, _) in _parse_srcset(srcset) {
+        if let Ok(resolved) = resolver.resolve(&url) {
+          images.insert(resolved);
         }
       }
     }
@@ -755,7 +2739,7 @@ fn _extract_images(html: &str, base_url: &str) -> Result<Vec<String>, Box<dyn st
     if let Ok(elements) = document.select(selector) {
       for element in elements {
         if let Some(content) = element.attributes.borrow().get("content") {
-          if let Ok(resolved) = resolve_image_url(content) {
+          if let Ok(resolved) = resolver.resolve(content) {
             images.insert(resolved);
           }
         }
@@ -774,7 +2758,7 @@ fn _extract_images(html: &str, base_url: &str) -> Result<Vec<String>, Box<dyn st
     if let Ok(elements) = document.select(selector) {
       for element in elements {
         if let Some(href) = element.attributes.borrow().get("href") {
-          if let Ok(resolved) = resolve_image_url(href) {
+          if let Ok(resolved) = resolver.resolve(href) {
             images.insert(resolved);
           }
         }
@@ -786,7 +2770,7 @@ fn _extract_images(html: &str, base_url: &str) -> Result<Vec<String>, Box<dyn st
   if let Ok(video_elements) = document.select("video[poster]") {
     for video in video_elements {
       if let Some(poster) = video.attributes.borrow().get("poster") {
-        if let Ok(resolved) = resolve_image_url(poster) {
+        if let Ok(resolved) = resolver.resolve(poster) {
           images.insert(resolved);
         }
       }
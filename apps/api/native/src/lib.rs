@@ -1,13 +1,19 @@
 #![deny(clippy::all)]
 
 pub use crate::crawler::*;
+pub use crate::frontier::*;
 pub use crate::html::*;
 pub use crate::pdf::*;
+pub use crate::robots::*;
+pub use crate::seen::*;
 pub use crate::utils::*;
 
 mod crawler;
+mod frontier;
 mod html;
 mod pdf;
+mod robots;
+mod seen;
 mod utils;
 
 pub use napi::bindgen_prelude::*;
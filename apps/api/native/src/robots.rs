@@ -0,0 +1,463 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+const DEFAULT_ROBOTS_USER_AGENT: &str = "FirecrawlAgent";
+
+#[derive(Deserialize)]
+#[napi(object)]
+pub struct ParseRobotsTxtCall {
+  pub robots_txt: String,
+  /// User-agent token to evaluate rules for. Defaults to `FirecrawlAgent`.
+  pub user_agent: Option<String>,
+  /// URL to find the matching rule for.
+  pub url: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[napi(object)]
+pub struct RobotsRequestRate {
+  pub requests: u32,
+  pub seconds: f64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[napi(object)]
+pub struct RobotsTxtRule {
+  pub allow: bool,
+  pub pattern: String,
+  /// 1-based line number of the rule in robots.txt.
+  pub line: u32,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[napi(object)]
+pub struct RobotsTxtGroup {
+  pub user_agents: Vec<String>,
+  pub rules: Vec<RobotsTxtRule>,
+  pub crawl_delay: Option<f64>,
+  pub request_rate: Option<RobotsRequestRate>,
+}
+
+#[derive(Serialize, Debug)]
+#[napi(object)]
+pub struct RobotsTxtInfo {
+  pub sitemaps: Vec<String>,
+  pub groups: Vec<RobotsTxtGroup>,
+  /// User-agent line of the group(s) that apply to the requested agent.
+  pub matched_user_agent: Option<String>,
+  pub crawl_delay: Option<f64>,
+  pub request_rate: Option<RobotsRequestRate>,
+  /// Whether `url` may be fetched. Only set when a URL was given.
+  pub allowed: Option<bool>,
+  /// Rule that decided `allowed`, if any rule matched.
+  pub matched_rule: Option<RobotsTxtRule>,
+}
+
+pub(crate) struct RobotsTxt {
+  pub(crate) sitemaps: Vec<String>,
+  pub(crate) groups: Vec<RobotsTxtGroup>,
+}
+
+/// Rules from all groups that apply to one user agent, merged the way
+/// Google's robots.txt spec describes.
+pub(crate) struct RobotsAgentRules<'a> {
+  pub(crate) user_agent: Option<&'a str>,
+  pub(crate) rules: Vec<&'a RobotsTxtRule>,
+  pub(crate) crawl_delay: Option<f64>,
+  pub(crate) request_rate: Option<RobotsRequestRate>,
+}
+
+fn parse_request_rate(value: &str) -> Option<RobotsRequestRate> {
+  let (requests, period) = value.split_whitespace().next()?.split_once('/')?;
+  let requests = requests.parse().ok()?;
+  let (period, unit) = match period.char_indices().last()? {
+    (i, 's') => (&period[..i], 1.0),
+    (i, 'm') => (&period[..i], 60.0),
+    (i, 'h') => (&period[..i], 3600.0),
+    (i, 'd') => (&period[..i], 86400.0),
+    _ => (period, 1.0),
+  };
+  let seconds: f64 = period.parse().ok()?;
+
+  Some(RobotsRequestRate {
+    requests,
+    seconds: seconds * unit,
+  })
+}
+
+/// Leading product token of a user-agent string, e.g. `FirecrawlAgent` for
+/// `FirecrawlAgent/1.0`. Only letters, `-` and `_` belong to it.
+fn product_token(user_agent: &str) -> &str {
+  let user_agent = user_agent.trim();
+  let end = user_agent
+    .find(|c: char| !(c.is_ascii_alphabetic() || c == '-' || c == '_'))
+    .unwrap_or(user_agent.len());
+  &user_agent[..end]
+}
+
+impl RobotsTxt {
+  pub(crate) fn parse(robots_txt: &str) -> Self {
+    let mut sitemaps = Vec::new();
+    let mut groups: Vec<RobotsTxtGroup> = Vec::new();
+    let mut in_agent_lines = false;
+
+    for (i, line) in robots_txt.lines().enumerate() {
+      let line = line.split('#').next().unwrap_or("").trim();
+      let Some((key, value)) = line.split_once(':') else {
+        continue;
+      };
+      let key = key.trim().to_lowercase();
+      let value = value.trim();
+
+      match key.as_str() {
+        "user-agent" | "useragent" | "user agent" => {
+          if !in_agent_lines || groups.is_empty() {
+            groups.push(RobotsTxtGroup {
+              user_agents: Vec::new(),
+              rules: Vec::new(),
+              crawl_delay: None,
+              request_rate: None,
+            });
+          }
+          if let Some(group) = groups.last_mut() {
+            group.user_agents.push(value.to_string());
+          }
+          in_agent_lines = true;
+        }
+        "allow" | "disallow" => {
+          in_agent_lines = false;
+          if let Some(group) = groups.last_mut() {
+            // An empty Disallow allows everything, so it is not a rule.
+            if !value.is_empty() {
+              group.rules.push(RobotsTxtRule {
+                allow: key == "allow",
+                pattern: value.to_string(),
+                line: i as u32 + 1,
+              });
+            }
+          }
+        }
+        "crawl-delay" => {
+          in_agent_lines = false;
+          if let Some(group) = groups.last_mut() {
+            group.crawl_delay = value.parse().ok();
+          }
+        }
+        "request-rate" => {
+          in_agent_lines = false;
+          if let Some(group) = groups.last_mut() {
+            group.request_rate = parse_request_rate(value);
+          }
+        }
+        "sitemap" | "site-map" if !value.is_empty() => {
+          sitemaps.push(value.to_string());
+        }
+        _ => {}
+      }
+    }
+
+    RobotsTxt { sitemaps, groups }
+  }
+
+  /// Collect the groups that apply to `user_agent`: the ones whose product
+  /// token equals its own, case-insensitively, falling back to `*`.
+  pub(crate) fn rules_for(&self, user_agent: &str) -> RobotsAgentRules<'_> {
+    let user_agent = product_token(user_agent);
+    let is_own = |agent: &String| {
+      let token = product_token(agent);
+      !token.is_empty() && token.eq_ignore_ascii_case(user_agent)
+    };
+    let is_wildcard = |agent: &String| agent == "*";
+
+    let agents = || self.groups.iter().flat_map(|x| x.user_agents.iter());
+    let own = agents().find(|x| is_own(x));
+    let applies = |agent: &String| match own {
+      Some(_) => is_own(agent),
+      None => is_wildcard(agent),
+    };
+    let matched = own.or_else(|| agents().find(|x| is_wildcard(x)));
+
+    let mut out = RobotsAgentRules {
+      user_agent: matched.map(|x| x.as_str()),
+      rules: Vec::new(),
+      crawl_delay: None,
+      request_rate: None,
+    };
+
+    for group in &self.groups {
+      if group.user_agents.iter().any(applies) {
+        out.rules.extend(group.rules.iter());
+        out.crawl_delay = out.crawl_delay.or(group.crawl_delay);
+        out.request_rate = out.request_rate.clone().or(group.request_rate.clone());
+      }
+    }
+
+    out
+  }
+
+  /// Pick the first of `user_agents` that has its own group in robots.txt,
+  /// falling back to the first one (which then gets the `*` rules).
+  pub(crate) fn preferred_user_agent<'a>(&self, user_agents: &'a [String]) -> &'a str {
+    user_agents
+      .iter()
+      .find(|agent| self.rules_for(agent).user_agent.is_some_and(|x| x != "*"))
+      .or(user_agents.first())
+      .map(|x| x.as_str())
+      .unwrap_or(DEFAULT_ROBOTS_USER_AGENT)
+  }
+}
+
+/// Percent-encode non-ASCII characters in a pattern so it compares against
+/// URL paths, which the `url` crate keeps encoded.
+fn encode_pattern(pattern: &str) -> std::borrow::Cow<'_, str> {
+  if pattern.is_ascii() {
+    return pattern.into();
+  }
+
+  let mut out = String::with_capacity(pattern.len() * 3);
+  for c in pattern.chars() {
+    if c.is_ascii() {
+      out.push(c);
+    } else {
+      for byte in c.encode_utf8(&mut [0; 4]).bytes() {
+        out.push_str(&format!("%{byte:02X}"));
+      }
+    }
+  }
+  out.into()
+}
+
+/// Match a robots.txt path pattern (`*` wildcards, trailing `$` anchor)
+/// against the start of `path`.
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+  let (pattern, anchored) = match pattern.strip_suffix('$') {
+    Some(x) => (x, true),
+    None => (pattern, false),
+  };
+
+  let mut parts = pattern.split('*');
+  let first = parts.next().unwrap_or("");
+  let Some(mut rest) = path.strip_prefix(first) else {
+    return false;
+  };
+
+  let parts: Vec<&str> = parts.collect();
+  for (i, part) in parts.iter().enumerate() {
+    let is_last = i == parts.len() - 1;
+    if is_last && anchored {
+      return rest.ends_with(part);
+    }
+    match rest.find(part) {
+      Some(pos) => rest = &rest[pos + part.len()..],
+      None => return false,
+    }
+  }
+
+  !anchored || rest.is_empty()
+}
+
+impl RobotsAgentRules<'_> {
+  /// Find the rule deciding whether `url` may be fetched: the longest
+  /// matching pattern wins, and `Allow` wins ties.
+  pub(crate) fn matched_rule(&self, url: &Url) -> Option<&RobotsTxtRule> {
+    let path = match url.query() {
+      Some(query) => format!("{}?{}", url.path(), query),
+      None => url.path().to_string(),
+    };
+
+    if path == "/robots.txt" {
+      return None;
+    }
+
+    self
+      .rules
+      .iter()
+      .filter(|rule| pattern_matches(&encode_pattern(&rule.pattern), &path))
+      .max_by(|a, b| {
+        a.pattern
+          .len()
+          .cmp(&b.pattern.len())
+          .then(a.allow.cmp(&b.allow))
+      })
+      .copied()
+  }
+}
+
+fn _parse_robots_txt(data: ParseRobotsTxtCall) -> std::result::Result<RobotsTxtInfo, String> {
+  let robots = RobotsTxt::parse(&data.robots_txt);
+  let user_agent = data
+    .user_agent
+    .unwrap_or_else(|| DEFAULT_ROBOTS_USER_AGENT.to_string());
+  let agent_rules = robots.rules_for(&user_agent);
+
+  let (allowed, matched_rule) = match data.url {
+    Some(url) => {
+      let url = Url::parse(&url).map_err(|e| format!("URL parse error: {e}"))?;
+      let rule = agent_rules.matched_rule(&url).cloned();
+      (Some(rule.as_ref().is_none_or(|x| x.allow)), rule)
+    }
+    None => (None, None),
+  };
+
+  Ok(RobotsTxtInfo {
+    matched_user_agent: agent_rules.user_agent.map(|x| x.to_string()),
+    crawl_delay: agent_rules.crawl_delay,
+    request_rate: agent_rules.request_rate.clone(),
+    allowed,
+    matched_rule,
+    sitemaps: robots.sitemaps,
+    groups: robots.groups,
+  })
+}
+
+/// Parse robots.txt into sitemaps, per-agent groups, crawl-delay and
+/// request-rate, optionally resolving the rule that applies to a URL.
+#[napi]
+pub fn parse_robots_txt(data: ParseRobotsTxtCall) -> Result<RobotsTxtInfo> {
+  _parse_robots_txt(data).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Parse robots.txt error: {e}"),
+    )
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const ROBOTS_TXT: &str = "# Example robots.txt
+User-agent: *
+Disallow: /private
+Allow: /private/public
+Crawl-delay: 5
+
+User-agent: FirecrawlAgent
+User-agent: OtherBot
+Disallow: /no-firecrawl
+Disallow: /*.pdf$
+Request-rate: 1/10s
+
+Sitemap: https://example.com/sitemap.xml
+Sitemap: https://example.com/news.xml
+";
+
+  fn call(user_agent: Option<&str>, url: Option<&str>) -> RobotsTxtInfo {
+    _parse_robots_txt(ParseRobotsTxtCall {
+      robots_txt: ROBOTS_TXT.to_string(),
+      user_agent: user_agent.map(|x| x.to_string()),
+      url: url.map(|x| x.to_string()),
+    })
+    .unwrap()
+  }
+
+  #[test]
+  fn test_parse_robots_txt_sitemaps_and_groups() {
+    let info = call(None, None);
+    assert_eq!(
+      info.sitemaps,
+      vec![
+        "https://example.com/sitemap.xml",
+        "https://example.com/news.xml"
+      ]
+    );
+    assert_eq!(info.groups.len(), 2);
+    assert_eq!(
+      info.groups[1].user_agents,
+      vec!["FirecrawlAgent", "OtherBot"]
+    );
+    assert_eq!(info.matched_user_agent.as_deref(), Some("FirecrawlAgent"));
+    assert_eq!(info.crawl_delay, None);
+    assert_eq!(
+      info.request_rate,
+      Some(RobotsRequestRate {
+        requests: 1,
+        seconds: 10.0
+      })
+    );
+    assert!(info.allowed.is_none());
+  }
+
+  #[test]
+  fn test_parse_robots_txt_wildcard_agent() {
+    let info = call(
+      Some("SomeBot/1.0"),
+      Some("https://example.com/private/page"),
+    );
+    assert_eq!(info.matched_user_agent.as_deref(), Some("*"));
+    assert_eq!(info.crawl_delay, Some(5.0));
+    assert_eq!(info.allowed, Some(false));
+    let rule = info.matched_rule.unwrap();
+    assert!(!rule.allow);
+    assert_eq!(rule.pattern, "/private");
+    assert_eq!(rule.line, 3);
+
+    let info = call(
+      Some("SomeBot"),
+      Some("https://example.com/private/public/x"),
+    );
+    assert_eq!(info.allowed, Some(true));
+    assert_eq!(info.matched_rule.unwrap().pattern, "/private/public");
+  }
+
+  #[test]
+  fn test_parse_robots_txt_matched_rule() {
+    let info = call(None, Some("https://example.com/docs/file.pdf"));
+    assert_eq!(info.allowed, Some(false));
+    assert_eq!(info.matched_rule.unwrap().line, 10);
+
+    let info = call(None, Some("https://example.com/docs/file.pdf?x=1"));
+    assert_eq!(info.allowed, Some(true));
+    assert!(info.matched_rule.is_none());
+
+    // The specific group replaces the `*` group entirely.
+    let info = call(None, Some("https://example.com/private"));
+    assert_eq!(info.allowed, Some(true));
+  }
+
+  #[test]
+  fn test_rules_for_whole_product_token() {
+    let robots = RobotsTxt::parse(
+      "User-agent: Fire\nDisallow: /fire\n\nUser-agent:\nDisallow: /empty\n\nUser-agent: *\nDisallow: /all\n\nUser-agent: firecrawlagent/2.0\nDisallow: /own\n",
+    );
+
+    let rules = robots.rules_for("FirecrawlAgent");
+    assert_eq!(rules.user_agent, Some("firecrawlagent/2.0"));
+    assert_eq!(
+      rules
+        .rules
+        .iter()
+        .map(|x| x.pattern.as_str())
+        .collect::<Vec<_>>(),
+      vec!["/own"]
+    );
+
+    let rules = robots.rules_for("OtherBot/1.0");
+    assert_eq!(rules.user_agent, Some("*"));
+    assert_eq!(
+      rules
+        .rules
+        .iter()
+        .map(|x| x.pattern.as_str())
+        .collect::<Vec<_>>(),
+      vec!["/all"]
+    );
+
+    assert_eq!(robots.rules_for("Fire").user_agent, Some("Fire"));
+  }
+
+  #[test]
+  fn test_pattern_matches() {
+    assert!(pattern_matches("/", "/anything"));
+    assert!(pattern_matches("/fish", "/fish.html"));
+    assert!(!pattern_matches("/fish", "/Fish"));
+    assert!(pattern_matches("/*.php$", "/folder/file.php"));
+    assert!(!pattern_matches("/*.php$", "/folder/file.php?x"));
+    assert!(pattern_matches("/fish*.php", "/fishheads/catfish.php"));
+    assert!(pattern_matches("/exact$", "/exact"));
+    assert!(!pattern_matches("/exact$", "/exactly"));
+    assert!(pattern_matches(&encode_pattern("/café"), "/caf%C3%A9/menu"));
+  }
+}
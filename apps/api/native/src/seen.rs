@@ -0,0 +1,519 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use serde::Deserialize;
+use std::{
+  cell::OnceCell,
+  collections::HashSet,
+  fs::{File, OpenOptions},
+  hash::{DefaultHasher, Hash, Hasher},
+  io::{Read, Seek, SeekFrom, Write},
+  path::PathBuf,
+  sync::atomic::{AtomicU64, Ordering},
+};
+
+const DEFAULT_EXPECTED_ITEMS: u32 = 1_000_000;
+const DEFAULT_FALSE_POSITIVE_RATE: f64 = 0.001;
+const DEFAULT_SPILL_THRESHOLD: u32 = 100_000;
+
+static SPILL_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Deserialize, Default)]
+#[napi(object)]
+pub struct SeenSetOptions {
+  /// `"exact"` (default) or `"bloom"`.
+  pub mode: Option<String>,
+  /// Bloom mode: number of URLs the filter is sized for. Defaults to 1,000,000.
+  pub expected_items: Option<u32>,
+  /// Bloom mode: false-positive rate at `expected_items`. Defaults to 0.001.
+  pub false_positive_rate: Option<f64>,
+  /// Exact mode: number of URLs held as strings before they are moved to the
+  /// spill file. Defaults to 100,000. Spilled URLs still cost 32 to 64 bytes
+  /// of memory each for their index slot, so memory grows with the crawl.
+  pub spill_threshold: Option<u32>,
+  /// Exact mode: file to spill to. It must not exist yet; it is created on
+  /// the first spill and left in place. Defaults to a temporary file that is
+  /// removed with the set.
+  pub spill_path: Option<String>,
+}
+
+#[inline]
+fn hash_with_seed(value: &str, seed: u64) -> u64 {
+  let mut hasher = DefaultHasher::new();
+  seed.hash(&mut hasher);
+  value.hash(&mut hasher);
+  hasher.finish()
+}
+
+/// Bloom filter using double hashing over two SipHash values.
+struct BloomFilter {
+  bits: Vec<u64>,
+  bit_count: u64,
+  hash_count: u32,
+}
+
+impl BloomFilter {
+  fn new(expected_items: u32, false_positive_rate: f64) -> std::result::Result<Self, String> {
+    if !(false_positive_rate > 0.0 && false_positive_rate < 1.0) {
+      return Err(format!(
+        "false positive rate must be between 0 and 1, got {false_positive_rate}"
+      ));
+    }
+
+    let n = expected_items.max(1) as f64;
+    let ln2 = std::f64::consts::LN_2;
+    let bit_count = ((-n * false_positive_rate.ln()) / (ln2 * ln2))
+      .ceil()
+      .max(64.0) as u64;
+    let hash_count = ((bit_count as f64 / n) * ln2).round().clamp(1.0, 32.0) as u32;
+
+    Ok(BloomFilter {
+      bits: vec![0; bit_count.div_ceil(64) as usize],
+      bit_count,
+      hash_count,
+    })
+  }
+
+  fn positions(&self, value: &str) -> impl Iterator<Item = u64> + '_ {
+    let h1 = hash_with_seed(value, 0);
+    let h2 = hash_with_seed(value, 1) | 1;
+    (0..self.hash_count as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % self.bit_count)
+  }
+
+  fn contains(&self, value: &str) -> bool {
+    self
+      .positions(value)
+      .all(|bit| self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+  }
+
+  fn insert(&mut self, value: &str) {
+    let positions: Vec<u64> = self.positions(value).collect();
+    for bit in positions {
+      self.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+    }
+  }
+}
+
+/// Slot of `SpillIndex`; `len == u32::MAX` marks an empty slot.
+#[derive(Clone, Copy)]
+struct IndexSlot {
+  hash: u64,
+  offset: u64,
+  len: u32,
+}
+
+const EMPTY_SLOT: IndexSlot = IndexSlot {
+  hash: 0,
+  offset: 0,
+  len: u32::MAX,
+};
+
+/// Open-addressed (linear probing) table from URL hash to its location in the
+/// spill file. Entries sharing a hash each take their own slot.
+struct SpillIndex {
+  slots: Vec<IndexSlot>,
+  len: usize,
+}
+
+impl SpillIndex {
+  fn new() -> Self {
+    SpillIndex {
+      slots: vec![EMPTY_SLOT; 1024],
+      len: 0,
+    }
+  }
+
+  fn insert(&mut self, slot: IndexSlot) {
+    // Keep the load factor under 3/4.
+    if (self.len + 1) * 4 > self.slots.len() * 3 {
+      let capacity = self.slots.len() * 2;
+      let old = std::mem::replace(&mut self.slots, vec![EMPTY_SLOT; capacity]);
+      for slot in old.into_iter().filter(|x| x.len != u32::MAX) {
+        self.place(slot);
+      }
+    }
+    self.place(slot);
+    self.len += 1;
+  }
+
+  fn place(&mut self, slot: IndexSlot) {
+    let mask = self.slots.len() - 1;
+    let mut i = slot.hash as usize & mask;
+    while self.slots[i].len != u32::MAX {
+      i = (i + 1) & mask;
+    }
+    self.slots[i] = slot;
+  }
+
+  /// Slots stored under `hash`.
+  fn candidates(&self, hash: u64) -> impl Iterator<Item = &IndexSlot> + '_ {
+    let mask = self.slots.len() - 1;
+    let start = hash as usize & mask;
+    (0..self.slots.len())
+      .map(move |i| &self.slots[(start + i) & mask])
+      .take_while(|slot| slot.len != u32::MAX)
+      .filter(move |slot| slot.hash == hash)
+  }
+}
+
+/// Append-only file of URLs with an in-memory index, so lookups read back only
+/// candidate entries. The index keeps a 24-byte slot per URL at a load factor
+/// between 3/8 and 3/4, i.e. 32 to 64 bytes of RAM per spilled URL.
+struct SpillFile {
+  file: File,
+  path: PathBuf,
+  remove_on_drop: bool,
+  index: SpillIndex,
+  end: u64,
+}
+
+impl SpillFile {
+  fn open(path: Option<&str>) -> std::io::Result<Self> {
+    let (path, remove_on_drop) = match path {
+      Some(path) => (PathBuf::from(path), false),
+      None => (
+        std::env::temp_dir().join(format!(
+          "firecrawl-seen-{}-{}",
+          std::process::id(),
+          SPILL_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+        )),
+        true,
+      ),
+    };
+
+    let file = OpenOptions::new()
+      .read(true)
+      .write(true)
+      .create_new(true)
+      .open(&path)?;
+
+    Ok(SpillFile {
+      file,
+      path,
+      remove_on_drop,
+      index: SpillIndex::new(),
+      end: 0,
+    })
+  }
+
+  fn append(&mut self, values: &[String]) -> std::io::Result<()> {
+    let mut buffer = Vec::new();
+    let mut entries = Vec::with_capacity(values.len());
+    for value in values {
+      entries.push((
+        hash_with_seed(value, 0),
+        self.end + buffer.len() as u64,
+        value.len() as u32,
+      ));
+      buffer.extend_from_slice(value.as_bytes());
+    }
+
+    self.file.seek(SeekFrom::Start(self.end))?;
+    self.file.write_all(&buffer)?;
+    self.end += buffer.len() as u64;
+
+    for (hash, offset, len) in entries {
+      self.index.insert(IndexSlot { hash, offset, len });
+    }
+    Ok(())
+  }
+
+  fn contains(&self, value: &str) -> std::io::Result<bool> {
+    let mut file = &self.file;
+    let mut buffer = Vec::new();
+    for slot in self.index.candidates(hash_with_seed(value, 0)) {
+      if slot.len as usize != value.len() {
+        continue;
+      }
+      buffer.resize(slot.len as usize, 0);
+      file.seek(SeekFrom::Start(slot.offset))?;
+      file.read_exact(&mut buffer)?;
+      if buffer == value.as_bytes() {
+        return Ok(true);
+      }
+    }
+    Ok(false)
+  }
+}
+
+impl Drop for SpillFile {
+  fn drop(&mut self) {
+    if self.remove_on_drop {
+      let _ = std::fs::remove_file(&self.path);
+    }
+  }
+}
+
+enum SeenStore {
+  Exact {
+    memory: HashSet<String>,
+    spill_threshold: usize,
+    spill_path: Option<String>,
+    spill: Option<SpillFile>,
+  },
+  Bloom(BloomFilter),
+}
+
+/// Set of already-seen URLs for large crawls. Exact mode keeps recent URLs in
+/// memory and moves older ones to a local file, keeping only a small index
+/// entry per URL; Bloom mode uses a fixed amount of memory but may report
+/// unseen URLs as seen at the configured false-positive rate.
+#[napi]
+pub struct SeenSet {
+  store: SeenStore,
+  len: u64,
+  /// First spill file error. Set through `&self` since lookups read the file.
+  spill_error: OnceCell<String>,
+}
+
+impl Default for SeenSet {
+  /// Exact set that never spills.
+  fn default() -> Self {
+    SeenSet {
+      store: SeenStore::Exact {
+        memory: HashSet::new(),
+        spill_threshold: usize::MAX,
+        spill_path: None,
+        spill: None,
+      },
+      len: 0,
+      spill_error: OnceCell::new(),
+    }
+  }
+}
+
+impl SeenSet {
+  pub(crate) fn compile(options: SeenSetOptions) -> std::result::Result<Self, String> {
+    if let Some(path) = options.spill_path.as_deref() {
+      if std::path::Path::new(path).exists() {
+        return Err(format!("spill path already exists: {path}"));
+      }
+    }
+
+    let store = match options.mode.as_deref().unwrap_or("exact") {
+      "exact" => SeenStore::Exact {
+        memory: HashSet::new(),
+        spill_threshold: options
+          .spill_threshold
+          .unwrap_or(DEFAULT_SPILL_THRESHOLD)
+          .max(1) as usize,
+        spill_path: options.spill_path,
+        spill: None,
+      },
+      "bloom" => SeenStore::Bloom(BloomFilter::new(
+        options.expected_items.unwrap_or(DEFAULT_EXPECTED_ITEMS),
+        options
+          .false_positive_rate
+          .unwrap_or(DEFAULT_FALSE_POSITIVE_RATE),
+      )?),
+      mode => return Err(format!("Unknown seen set mode: {mode}")),
+    };
+
+    Ok(SeenSet {
+      store,
+      len: 0,
+      spill_error: OnceCell::new(),
+    })
+  }
+
+  /// Whether `url` was added. A spill file read error is recorded in
+  /// `spill_error` and the URL is reported as unseen.
+  pub(crate) fn contains(&self, url: &str) -> bool {
+    match &self.store {
+      SeenStore::Exact { memory, spill, .. } => {
+        memory.contains(url)
+          || spill.as_ref().is_some_and(|x| {
+            x.contains(url).unwrap_or_else(|e| {
+              let _ = self.spill_error.set(e.to_string());
+              false
+            })
+          })
+      }
+      SeenStore::Bloom(bloom) => bloom.contains(url),
+    }
+  }
+
+  /// Number of URLs added, as reported by `size`.
+  #[inline]
+  pub(crate) fn len(&self) -> u64 {
+    self.len
+  }
+
+  /// Add `url`, returning whether it was not seen before.
+  pub(crate) fn insert(&mut self, url: &str) -> bool {
+    if self.contains(url) {
+      return false;
+    }
+
+    match &mut self.store {
+      SeenStore::Exact {
+        memory,
+        spill_threshold,
+        spill_path,
+        spill,
+      } => {
+        memory.insert(url.to_string());
+
+        // If spilling fails, everything stays in memory and the error is
+        // surfaced through `spillError`.
+        if memory.len() > *spill_threshold && self.spill_error.get().is_none() {
+          let spilled = match spill {
+            Some(spill) => Ok(spill),
+            None => SpillFile::open(spill_path.as_deref()).map(|x| spill.insert(x)),
+          }
+          .and_then(|spill| {
+            let values: Vec<String> = memory.iter().cloned().collect();
+            spill.append(&values)
+          });
+
+          match spilled {
+            Ok(()) => memory.clear(),
+            Err(e) => {
+              let _ = self.spill_error.set(e.to_string());
+            }
+          }
+        }
+      }
+      SeenStore::Bloom(bloom) => bloom.insert(url),
+    }
+
+    self.len += 1;
+    true
+  }
+}
+
+#[napi]
+impl SeenSet {
+  #[napi(constructor)]
+  pub fn new(options: Option<SeenSetOptions>) -> Result<Self> {
+    SeenSet::compile(options.unwrap_or_default())
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Seen set error: {e}")))
+  }
+
+  /// Add a URL, returning `true` if it was not seen before. `filter_links`
+  /// stores URLs as returned by `normalize_url`.
+  #[napi(js_name = "insert")]
+  pub fn insert_url(&mut self, url: String) -> bool {
+    self.insert(&url)
+  }
+
+  #[napi(js_name = "contains")]
+  pub fn contains_url(&self, url: String) -> bool {
+    self.contains(&url)
+  }
+
+  /// Number of URLs added. In Bloom mode, inserting an unseen URL that is a
+  /// false positive returns `false` and does not count, so this can be lower
+  /// than the number of distinct URLs inserted.
+  #[napi(getter)]
+  pub fn size(&self) -> f64 {
+    self.len as f64
+  }
+
+  /// First error reading or writing the spill file, if any. Nothing more is
+  /// spilled once it is set.
+  #[napi(getter)]
+  pub fn spill_error(&self) -> Option<String> {
+    self.spill_error.get().cloned()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_seen_set_exact_spills_to_disk() {
+    let mut seen = SeenSet::compile(SeenSetOptions {
+      spill_threshold: Some(10),
+      ..Default::default()
+    })
+    .unwrap();
+
+    for i in 0..100 {
+      assert!(seen.insert(&format!("https://example.com/{i}")));
+    }
+    for i in 0..100 {
+      assert!(seen.contains(&format!("https://example.com/{i}")));
+      assert!(!seen.insert(&format!("https://example.com/{i}")));
+    }
+    assert!(!seen.contains("https://example.com/100"));
+    assert!(!seen.contains("https://example.com/1000"));
+    assert_eq!(seen.size(), 100.0);
+    assert_eq!(seen.spill_error(), None);
+
+    let SeenStore::Exact { memory, spill, .. } = &seen.store else {
+      panic!("expected exact store");
+    };
+    assert!(memory.len() <= 10);
+    let path = spill.as_ref().unwrap().path.clone();
+    assert!(path.exists());
+
+    drop(seen);
+    assert!(!path.exists());
+  }
+
+  #[test]
+  fn test_seen_set_bloom() {
+    let mut seen = SeenSet::compile(SeenSetOptions {
+      mode: Some("bloom".to_string()),
+      expected_items: Some(10_000),
+      false_positive_rate: Some(0.01),
+      ..Default::default()
+    })
+    .unwrap();
+
+    for i in 0..10_000 {
+      seen.insert(&format!("https://example.com/{i}"));
+    }
+    for i in 0..10_000 {
+      assert!(seen.contains(&format!("https://example.com/{i}")));
+    }
+
+    let false_positives = (10_000..20_000)
+      .filter(|i| seen.contains(&format!("https://example.com/{i}")))
+      .count();
+    assert!(false_positives < 300, "{false_positives} false positives");
+  }
+
+  #[test]
+  fn test_seen_set_invalid_options() {
+    assert!(SeenSet::compile(SeenSetOptions {
+      mode: Some("cuckoo".to_string()),
+      ..Default::default()
+    })
+    .is_err());
+    assert!(SeenSet::compile(SeenSetOptions {
+      mode: Some("bloom".to_string()),
+      false_positive_rate: Some(1.5),
+      ..Default::default()
+    })
+    .is_err());
+
+    let path = std::env::temp_dir().join(format!("firecrawl-seen-test-{}", std::process::id()));
+    std::fs::write(&path, "keep").unwrap();
+    assert!(SeenSet::compile(SeenSetOptions {
+      spill_path: Some(path.to_string_lossy().to_string()),
+      ..Default::default()
+    })
+    .is_err());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep");
+    std::fs::remove_file(&path).unwrap();
+  }
+
+  #[test]
+  fn test_spill_index_collisions() {
+    let mut index = SpillIndex::new();
+    for i in 0..5000u64 {
+      index.insert(IndexSlot {
+        hash: i % 100,
+        offset: i,
+        len: 1,
+      });
+    }
+    assert!(index.slots.len() >= 5000 * 4 / 3);
+    let offsets: Vec<u64> = index.candidates(42).map(|x| x.offset).collect();
+    assert_eq!(offsets.len(), 50);
+    assert!(offsets.iter().all(|x| x % 100 == 42));
+    assert_eq!(index.candidates(100).count(), 0);
+  }
+}
@@ -896,6 +896,18 @@ export type Document = {
     publishedTime?: string;
     articleTag?: string;
     articleSection?: string;
+    canonicalUrl?: string;
+    hreflangAlternates?: { hreflang: string; href: string }[];
+    feeds?: { href: string; type: string; title?: string }[];
+    twitterCard?: string;
+    twitterSite?: string;
+    twitterCreator?: string;
+    twitterTitle?: string;
+    twitterDescription?: string;
+    twitterImage?: string;
+    nextUrl?: string;
+    prevUrl?: string;
+    refresh?: { delay: number; url?: string };
     url?: string;
     sourceURL?: string;
     statusCode: number;
@@ -1,7 +1,14 @@
 import {
+  contentFingerprint,
+  contentSimilarity,
   extractLinks,
   extractMetadata,
+  extractStructuredData,
+  htmlToMarkdown,
+  HtmlTransformStream,
   transformHtml,
+  transformHtmlStream,
+  transformHtmlWithReport,
   TransformHtmlOptions,
 } from "@mendable/firecrawl-rs";
 
@@ -263,6 +270,140 @@ describe("HTML Transformer", () => {
     });
   });
 
+  describe("extractMetadata link and twitter fields", () => {
+    it("should extract canonical, alternates, feeds, pagination and refresh", async () => {
+      const metadata = await extractMetadata(`
+        <html>
+          <head>
+            <link rel="canonical" href="https://example.com/article">
+            <link rel="alternate" hreflang="de" href="https://example.com/de/article">
+            <link rel="alternate" hreflang="x-default" href="https://example.com/article">
+            <link rel="alternate" type="application/rss+xml" title="Blog" href="/feed.xml">
+            <link rel="alternate" type="application/atom+xml" href="/atom.xml">
+            <link rel="next" href="/article?page=3">
+            <link rel="prev" href="/article?page=1">
+            <meta http-equiv="refresh" content="5; url=/moved">
+            <meta name="twitter:card" content="summary_large_image">
+            <meta property="twitter:title" content="Twitter Title">
+          </head>
+        </html>
+      `);
+
+      expect(metadata).toMatchObject({
+        canonicalUrl: "https://example.com/article",
+        hreflangAlternates: [
+          { hreflang: "de", href: "https://example.com/de/article" },
+          { hreflang: "x-default", href: "https://example.com/article" },
+        ],
+        feeds: [
+          { href: "/feed.xml", type: "application/rss+xml", title: "Blog" },
+          { href: "/atom.xml", type: "application/atom+xml" },
+        ],
+        nextUrl: "/article?page=3",
+        prevUrl: "/article?page=1",
+        refresh: { delay: 5, url: "/moved" },
+        twitterCard: "summary_large_image",
+        twitterTitle: "Twitter Title",
+      });
+    });
+  });
+
+  describe("extractStructuredData", () => {
+    it("should parse JSON-LD blocks and report invalid ones", () => {
+      const result = extractStructuredData(
+        `
+        <script type="application/ld+json">
+          {"@context": "https://schema.org", "@type": "Article", "headline": "Title"}
+        </script>
+        <script type="application/ld+json">{ not json</script>
+        `,
+        "https://example.com",
+      );
+
+      expect(result.jsonLd).toEqual([
+        {
+          "@context": "https://schema.org",
+          "@type": "Article",
+          headline: "Title",
+        },
+      ]);
+      expect(result.errors).toHaveLength(1);
+    });
+
+    it("should build Microdata item trees", () => {
+      const result = extractStructuredData(
+        `
+        <div itemscope itemtype="https://schema.org/Product" itemref="desc">
+          <span itemprop="name">Widget</span>
+          <img itemprop="image" src="/widget.png">
+          <div itemprop="offers" itemscope itemtype="https://schema.org/Offer">
+            <meta itemprop="price" content="9.99">
+            <span itemprop="priceCurrency">USD</span>
+          </div>
+        </div>
+        <p id="desc" itemprop="description">A fine widget</p>
+        `,
+        "https://example.com/shop/",
+      );
+
+      expect(result.microdata).toEqual([
+        {
+          type: ["https://schema.org/Product"],
+          properties: {
+            name: ["Widget"],
+            image: ["https://example.com/widget.png"],
+            description: ["A fine widget"],
+            offers: [
+              {
+                type: ["https://schema.org/Offer"],
+                properties: { price: ["9.99"], priceCurrency: ["USD"] },
+              },
+            ],
+          },
+        },
+      ]);
+    });
+
+    it("should resolve RDFa types against vocab and prefixes", () => {
+      const result = extractStructuredData(
+        `
+        <div vocab="https://schema.org/" typeof="Event">
+          <span property="name">Conference</span>
+          <time property="startDate" datetime="2026-05-01">May 1</time>
+          <div property="location" typeof="Place">
+            <span property="name">Main Hall</span>
+          </div>
+        </div>
+        <div prefix="ex: http://example.org/ns#" typeof="ex:Thing" resource="/thing">
+          <span property="ex:label">Thing</span>
+        </div>
+        `,
+        "https://example.com",
+      );
+
+      expect(result.rdfa).toEqual([
+        {
+          type: ["https://schema.org/Event"],
+          properties: {
+            name: ["Conference"],
+            startDate: ["2026-05-01"],
+            location: [
+              {
+                type: ["https://schema.org/Place"],
+                properties: { name: ["Main Hall"] },
+              },
+            ],
+          },
+        },
+        {
+          type: ["http://example.org/ns#Thing"],
+          id: "https://example.com/thing",
+          properties: { "ex:label": ["Thing"] },
+        },
+      ]);
+    });
+  });
+
   describe("transformHtml", () => {
     it("should transform HTML content according to options", async () => {
       const options: TransformHtmlOptions = {
@@ -441,5 +582,454 @@ describe("HTML Transformer", () => {
       expect(result).toContain("https://example.com/#q1");
       expect(result).toContain("https://example.com/#q2");
     });
+
+    it("should keep only the highest-scoring block in readability mode", async () => {
+      const html = `
+        <div class="topbar">
+          <a href="/">Home</a> <a href="/shop">Shop</a> <a href="/blog">Blog</a>
+        </div>
+        <div class="wrapper">
+          <div class="lhs-links">
+            <ul>
+              <li><a href="/a">Category A with a long name</a></li>
+              <li><a href="/b">Category B with a long name</a></li>
+            </ul>
+          </div>
+          <div class="story-body">
+            <h1>How we rebuilt our crawler</h1>
+            <p>Crawling the web at scale is hard, and over the past year we rewrote most of our pipeline in Rust, which cut memory use dramatically.</p>
+            <p>The first step was moving link filtering out of JavaScript, because the regex engine, the robots parser and the URL handling all had to agree.</p>
+            <p>We then added a frontier, a seen set and a fingerprinting step, so duplicate pages never reach the expensive parts of the system.</p>
+          </div>
+          <div class="copyright-strip">
+            Copyright 2024 Example Inc, <a href="/privacy">Privacy</a>, <a href="/terms">Terms</a>
+          </div>
+        </div>
+      `;
+      const options: TransformHtmlOptions = {
+        html,
+        url: "https://example.com",
+        includeTags: [],
+        excludeTags: [],
+        onlyMainContent: true,
+        mainContentMode: "readability",
+      };
+
+      const result = await transformHtml(options);
+      expect(result).toContain("How we rebuilt our crawler");
+      expect(result).toContain("a seen set");
+      expect(result).not.toContain("Category A");
+      expect(result).not.toContain("Shop");
+      expect(result).not.toContain("Copyright");
+
+      const selectors = await transformHtml({
+        ...options,
+        mainContentMode: undefined,
+      });
+      expect(selectors).toContain("Category A");
+    });
+
+    it("should apply custom remove and force-include selectors", async () => {
+      const html = `
+        <nav>Menu</nav>
+        <div class="promo-bar">Sale</div>
+        <aside><p class="keep-me">Author bio</p></aside>
+        <p>Body</p>
+      `;
+      const options: TransformHtmlOptions = {
+        html,
+        url: "https://example.com",
+        includeTags: [],
+        excludeTags: [],
+        onlyMainContent: true,
+        removeSelectors: [".promo-bar"],
+        forceIncludeSelectors: [".keep-me"],
+      };
+
+      const extended = await transformHtml(options);
+      expect(extended).not.toContain("Menu");
+      expect(extended).not.toContain("Sale");
+      expect(extended).toContain("Author bio");
+      expect(extended).toContain("Body");
+
+      const replaced = await transformHtml({
+        ...options,
+        forceIncludeSelectors: undefined,
+        replaceDefaultSelectors: true,
+      });
+      expect(replaced).toContain("Menu");
+      expect(replaced).not.toContain("Sale");
+      expect(replaced).toContain("Author bio");
+    });
+
+    it("should reject unknown main content modes", () => {
+      expect(() =>
+        transformHtml({
+          html: "<p>Test</p>",
+          url: "https://example.com",
+          includeTags: [],
+          excludeTags: [],
+          onlyMainContent: true,
+          mainContentMode: "magic",
+        }),
+      ).toThrow();
+    });
+
+    it("should absolutize all URL-bearing attributes when asked", async () => {
+      const result = await transformHtml({
+        html: `
+          <picture>
+            <source srcset="image.webp 1x, image@2x.webp 2x">
+            <img src="image.png">
+          </picture>
+          <video poster="poster.jpg"><source src="/video.mp4"></video>
+          <iframe src="embed"></iframe>
+          <form action="submit"></form>
+          <div style="background-image: url('bg.png')">Hero</div>
+        `,
+        url: "https://example.com/blog/",
+        includeTags: [],
+        excludeTags: [],
+        onlyMainContent: false,
+        absolutizeAllUrls: true,
+      });
+
+      expect(result).toContain(
+        'srcset="https://example.com/blog/image.webp 1x, https://example.com/blog/image@2x.webp 2x"',
+      );
+      expect(result).toContain('poster="https://example.com/blog/poster.jpg"');
+      expect(result).toContain('src="https://example.com/video.mp4"');
+      expect(result).toContain('src="https://example.com/blog/embed"');
+      expect(result).toContain('action="https://example.com/blog/submit"');
+      expect(result).toContain("url('https://example.com/blog/bg.png')");
+    });
+
+    it("should keep fragment-only CSS url() references", async () => {
+      const result = await transformHtml({
+        html: `<svg><rect style="filter: url(#blur); fill: url('#grad')"></rect></svg>`,
+        url: "https://example.com/blog/",
+        includeTags: [],
+        excludeTags: [],
+        onlyMainContent: false,
+        absolutizeAllUrls: true,
+      });
+
+      expect(result).toContain("filter: url(#blur); fill: url('#grad')");
+    });
+
+    describe("srcset policies", () => {
+      const html = `<img src="fallback.jpg" srcset="https://cdn.example.com/w_200,h_100/a.jpg 200w, https://cdn.example.com/w_800,h_400/a.jpg 800w, https://cdn.example.com/w_400,h_200/a.jpg 400w">`;
+      const transform = (
+        options: Pick<
+          TransformHtmlOptions,
+          "srcsetPolicy" | "srcsetTargetWidth"
+        >,
+      ) =>
+        transformHtml({
+          html,
+          url: "https://example.com",
+          includeTags: [],
+          excludeTags: [],
+          onlyMainContent: false,
+          ...options,
+        });
+
+      it("should pick the largest candidate by default", async () => {
+        expect(await transform({})).toContain(
+          'src="https://cdn.example.com/w_800,h_400/a.jpg"',
+        );
+      });
+
+      it("should pick the smallest candidate", async () => {
+        expect(await transform({ srcsetPolicy: "smallest" })).toContain(
+          'src="https://cdn.example.com/w_200,h_100/a.jpg"',
+        );
+      });
+
+      it("should pick the candidate closest to the target width", async () => {
+        expect(
+          await transform({ srcsetPolicy: "closest", srcsetTargetWidth: 350 }),
+        ).toContain('src="https://cdn.example.com/w_400,h_200/a.jpg"');
+        expect(() => transform({ srcsetPolicy: "closest" })).toThrow();
+      });
+
+      it("should keep every candidate, absolutized", async () => {
+        const result = await transformHtml({
+          html: '<img src="a.jpg" srcset="a.jpg, b.jpg 2x,c.jpg 3x">',
+          url: "https://example.com/blog/",
+          includeTags: [],
+          excludeTags: [],
+          onlyMainContent: false,
+          srcsetPolicy: "keep",
+        });
+        expect(result).toContain('src="https://example.com/blog/a.jpg"');
+        expect(result).toContain(
+          'srcset="https://example.com/blog/a.jpg, https://example.com/blog/b.jpg 2x, https://example.com/blog/c.jpg 3x"',
+        );
+      });
+    });
+
+    it("should report what each step removed", () => {
+      const report = transformHtmlWithReport({
+        html: `
+          <html>
+            <head><title>Page</title></head>
+            <body>
+              <nav><a href="/">Home page</a><div class="menu">Menu</div></nav>
+              <main>
+                <p>Hello world</p>
+                <div class="ad">Buy now</div>
+                <div class="ad">Sale</div>
+              </main>
+              <footer>Footer</footer>
+            </body>
+          </html>
+        `,
+        url: "https://example.com",
+        includeTags: [],
+        excludeTags: [".ad"],
+        onlyMainContent: true,
+      });
+
+      expect(report.html).toContain("Hello world");
+      expect(report.html).not.toContain("Buy now");
+      expect(report.removals).toEqual(
+        expect.arrayContaining([
+          { stage: "cleanup", rule: "head", nodes: 1, textLength: 4 },
+          { stage: "exclude_tags", rule: ".ad", nodes: 2, textLength: 11 },
+          { stage: "main_content", rule: "nav", nodes: 1, textLength: 13 },
+          { stage: "main_content", rule: "footer", nodes: 1, textLength: 6 },
+        ]),
+      );
+    });
+
+    it("should not remove anything in readability mode without a candidate", () => {
+      const report = transformHtmlWithReport({
+        html: `<body><div class="sidebar">Short</div><p>Tiny</p></body>`,
+        url: "https://example.com",
+        includeTags: [],
+        excludeTags: [],
+        onlyMainContent: true,
+        mainContentMode: "readability",
+      });
+
+      expect(report.html).toContain("Tiny");
+      expect(report.removals.filter(x => x.stage === "readability")).toEqual([]);
+    });
+  });
+
+  describe("transformHtmlStream", () => {
+    it("should strip non-content tags and absolutize links", () => {
+      const result = transformHtmlStream({
+        html: `
+          <html>
+            <head><base href="/docs/"><title>Page</title></head>
+            <body>
+              <script>track()</script>
+              <style>p { color: red; }</style>
+              <div class="ad">Advertisement</div>
+              <p><a href="page">Link</a><img src="image.png"></p>
+            </body>
+          </html>
+        `,
+        url: "https://example.com/blog/",
+        excludeTags: [".ad"],
+      });
+
+      expect(result).not.toContain("<title>");
+      expect(result).not.toContain("track()");
+      expect(result).not.toContain("color: red");
+      expect(result).not.toContain("Advertisement");
+      expect(result).toContain('href="https://example.com/docs/page"');
+      expect(result).toContain('src="https://example.com/docs/image.png"');
+    });
+
+    it("should reject invalid exclude selectors", () => {
+      expect(() =>
+        transformHtmlStream({
+          html: "<p>Test</p>",
+          url: "https://example.com",
+          excludeTags: ["[["],
+        }),
+      ).toThrow();
+    });
+
+    it("should emit output for each written chunk", () => {
+      const html = `<html><head><base href="/docs/"></head><body><script>track()</script>${"<p><a href=\"page\">Link</a></p>".repeat(100)}</body></html>`;
+      const stream = new HtmlTransformStream({
+        url: "https://example.com/blog/",
+        excludeTags: [],
+      });
+
+      const chunks: Buffer[] = [];
+      for (let i = 0; i < html.length; i += 64) {
+        chunks.push(stream.write(Buffer.from(html.slice(i, i + 64))));
+      }
+      expect(chunks.filter(x => x.length > 0).length).toBeGreaterThan(1);
+      chunks.push(stream.end());
+
+      expect(Buffer.concat(chunks).toString()).toBe(
+        transformHtmlStream({
+          html,
+          url: "https://example.com/blog/",
+          excludeTags: [],
+        }),
+      );
+      expect(() => stream.end()).toThrow();
+    });
+  });
+
+  describe("htmlToMarkdown", () => {
+    it("should convert headings, emphasis and lists", async () => {
+      const html =
+        "<h1>Title</h1><div><p>Hello <strong>bold</strong> world!</p><ul><li>List item</li></ul></div>";
+      const markdown = await htmlToMarkdown(html);
+      expect(markdown).toBe(
+        "# Title\n\nHello **bold** world!\n\n- List item",
+      );
+    });
+
+    it("should resolve relative links and images against the base href", async () => {
+      const html = `
+        <html>
+          <head><base href="/docs/"></head>
+          <body>
+            <a href="page?a=1">Page</a>
+            <img src="img.png" alt="Logo">
+          </body>
+        </html>
+      `;
+      const markdown = await htmlToMarkdown(html, "https://example.com/a/b");
+      expect(markdown).toContain("[Page](https://example.com/docs/page?a=1)");
+      expect(markdown).toContain("![Logo](https://example.com/docs/img.png)");
+    });
+
+    it("should convert nested and ordered lists", async () => {
+      const html =
+        '<ol start="3"><li>One<ul><li>Nested</li></ul></li><li>Two</li></ol>';
+      const markdown = await htmlToMarkdown(html);
+      expect(markdown).toBe("3. One\n   - Nested\n4. Two");
+    });
+
+    it("should convert code blocks with their language", async () => {
+      const html =
+        '<pre><code class="language-js">const a = 1;\nconsole.log(a);\n</code></pre><p>Use <code>a</code></p>';
+      const markdown = await htmlToMarkdown(html);
+      expect(markdown).toBe(
+        "```js\nconst a = 1;\nconsole.log(a);\n```\n\nUse `a`",
+      );
+    });
+
+    it("should convert tables", async () => {
+      const html = `
+        <table>
+          <thead><tr><th>Name</th><th>Value</th></tr></thead>
+          <tbody><tr><td>a|b</td><td>1</td></tr></tbody>
+        </table>
+      `;
+      const markdown = await htmlToMarkdown(html);
+      expect(markdown).toBe(
+        "| Name | Value |\n| --- | --- |\n| a\\|b | 1 |",
+      );
+    });
+
+    it("should escape Markdown characters in text", async () => {
+      const html =
+        "<p>2 * 3, snake_case and [x]</p><p># not a heading</p><p>1. not a list</p>";
+      const markdown = await htmlToMarkdown(html);
+      expect(markdown).toBe(
+        "2 \\* 3, snake\\_case and \\[x\\]\n\n\\# not a heading\n\n1\\. not a list",
+      );
+    });
+
+    it("should only escape line-start markers that Markdown would parse", async () => {
+      const html =
+        "<p>-5 degrees</p><p>+1</p><p>#hashtag</p><p>- item</p><p>===</p>";
+      const markdown = await htmlToMarkdown(html);
+      expect(markdown).toBe(
+        "-5 degrees\n\n+1\n\n#hashtag\n\n\\- item\n\n\\===",
+      );
+    });
+
+    it("should drop scripts and styles", async () => {
+      const html =
+        "<p>Content</p><script>alert('x')</script><style>p { color: red; }</style>";
+      const markdown = await htmlToMarkdown(html);
+      expect(markdown).toBe("Content");
+    });
+
+    it("should fall back to text for deeply nested content", async () => {
+      const html = `${"<div>".repeat(5000)}<p>Deep <b>text</b></p>${"</div>".repeat(5000)}`;
+      const markdown = await htmlToMarkdown(html);
+      expect(markdown).toBe("Deep text");
+    });
+  });
+
+  describe("contentFingerprint", () => {
+    const page = (nav: string, body: string) => `
+      <html>
+        <head><title>Page</title></head>
+        <body>
+          <nav>${nav}</nav>
+          <main><h1>Product</h1><p>${body}</p></main>
+          <script>var tracking = 1;</script>
+        </body>
+      </html>
+    `;
+    const shoe =
+      "This lightweight running shoe has a breathable mesh upper, a cushioned midsole and a durable rubber outsole for everyday training on roads and trails.";
+
+    it("should treat pages differing only in navigation as near-duplicates", async () => {
+      const a = await contentFingerprint(page("Home Shop Cart", shoe));
+      const b = await contentFingerprint(page("Home Shop Cart Sale", shoe));
+      const similarity = await contentSimilarity(a, b);
+
+      expect(a.simhash).toHaveLength(16);
+      expect(a.minhash).toHaveLength(64);
+      expect(similarity.jaccard).toBeGreaterThan(0.9);
+      expect(similarity.simhashDistance).toBeLessThan(5);
+    });
+
+    it("should tell different pages apart", async () => {
+      const a = await contentFingerprint(page("Home", shoe));
+      const b = await contentFingerprint(
+        page(
+          "Home",
+          "Our company was founded in 1999 and we build enterprise database software for banks and insurance companies worldwide.",
+        ),
+      );
+      const similarity = await contentSimilarity(a, b);
+
+      expect(similarity.jaccard).toBeLessThan(0.2);
+      expect(similarity.simhashDistance).toBeGreaterThan(15);
+    });
+
+    it("should fingerprint plain text", async () => {
+      const fingerprint = await contentFingerprint("Hello, World!");
+      expect(fingerprint.tokenCount).toBe(2);
+    });
+
+    it("should produce stable fingerprints", async () => {
+      const fingerprint = await contentFingerprint(
+        "The quick brown fox jumps over the lazy dog",
+      );
+      expect(fingerprint.simhash).toBe("e92e77ce80c18875");
+      expect(fingerprint.minhash.slice(0, 4)).toEqual([
+        151197278, 30582125, 134592641, 769880796,
+      ]);
+    });
+
+    it("should reject fingerprints of different sizes", async () => {
+      const a = await contentFingerprint(shoe);
+      const b = await contentFingerprint(shoe, { minhashSize: 32 });
+      expect(() => contentSimilarity(a, b)).toThrow();
+    });
+
+    it("should cap the number of MinHash values", async () => {
+      const fingerprint = await contentFingerprint(shoe, {
+        minhashSize: 4294967295,
+      });
+      expect(fingerprint.minhash).toHaveLength(1024);
+    });
   });
 });
@@ -6,6 +6,7 @@ import dotenv from "dotenv";
 import { logger } from "./logger";
 import { stat } from "fs/promises";
 import { HTML_TO_MARKDOWN_PATH } from "../natives";
+import { htmlToMarkdown } from "@mendable/firecrawl-rs";
 dotenv.config();
 
 // TODO: add a timeout to the Go parser
@@ -52,11 +53,26 @@ class GoMarkdownConverter {
 
 export async function parseMarkdown(
   html: string | null | undefined,
+  url?: string,
 ): Promise<string> {
   if (!html) {
     return "";
   }
 
+  if (process.env.USE_RUST_MARKDOWN_PARSER == "true") {
+    try {
+      let markdownContent = await htmlToMarkdown(html, url);
+      markdownContent = processMultiLineLinks(markdownContent);
+      markdownContent = removeSkipToContentLinks(markdownContent);
+      return markdownContent;
+    } catch (error) {
+      Sentry.captureException(error);
+      logger.error(
+        `Error converting HTML to Markdown with Rust parser: ${error}`,
+      );
+    }
+  }
+
   try {
     if (process.env.USE_GO_MARKDOWN_PARSER == "true") {
       const converter = await GoMarkdownConverter.getInstance();
@@ -15,7 +15,7 @@ import {
 } from "../../lib/robots-txt";
 import { ScrapeJobTimeoutError } from "../../lib/error";
 import { ScrapeOptions } from "../../controllers/v2/types";
-import { filterLinks, filterUrl } from "@mendable/firecrawl-rs";
+import { filterLinks, filterUrl, UrlRuleOptions } from "@mendable/firecrawl-rs";
 
 export const SITEMAP_LIMIT = 100;
 
@@ -36,6 +36,7 @@ enum DenialReason {
   SOCIAL_MEDIA = "URL is a social media or email link",
   EXTERNAL_LINK = "External URL not allowed",
   SECTION_LINK = "URL contains section anchor (#)",
+  DUPLICATE = "URL is a duplicate of an already accepted link",
 }
 
 interface FilterLinksResult {
@@ -165,20 +166,25 @@ export class WebCrawler {
     try {
       const res = await filterLinks({
         links: sitemapLinks,
-        limit: isFinite(limit) ? limit : undefined,
-        maxDepth: maxDepth,
-        baseUrl: this.baseUrl,
-        initialUrl: this.initialUrl,
-        regexOnFullUrl: this.regexOnFullURL,
-        excludes: this.excludes,
-        includes: this.includes,
-        allowBackwardCrawling: this.allowBackwardCrawling,
-        ignoreRobotsTxt: this.ignoreRobotsTxt,
-        robotsTxt: this.robotsTxt,
-        allowExternalContentLinks: this.allowExternalContentLinks,
-        allowSubdomains: this.allowSubdomains,
+        options: {
+          rules: this.urlRuleOptions(),
+          limit: isFinite(limit) ? limit : undefined,
+          maxDepth: maxDepth,
+          initialUrl: this.initialUrl,
+          regexOnFullUrl: this.regexOnFullURL,
+          includes: this.includes,
+          allowBackwardCrawling: this.allowBackwardCrawling,
+          explainDenials: !!process.env.FIRECRAWL_DEBUG_FILTER_LINKS,
+        },
       });
 
+      if (Object.keys(res.invalidPatterns).length > 0) {
+        this.logger.warn("Ignoring invalid include/exclude patterns", {
+          method: "filterLinks",
+          invalidPatterns: res.invalidPatterns,
+        });
+      }
+
       const fancyDenialReasons = new Map<string, string>();
       Object.entries(res.denialReasons).forEach(([key, value]) => {
         fancyDenialReasons.set(key, DenialReason[value]);
@@ -190,7 +196,9 @@ export class WebCrawler {
         }
 
         for (const [link, reason] of fancyDenialReasons) {
-          this.logger.debug(`${link} ${reason}`);
+          this.logger.debug(`${link} ${reason}`, {
+            details: res.denialDetails?.[link],
+          });
         }
       }
 
@@ -534,16 +542,22 @@ export class WebCrawler {
     }
   }
 
-  public async filterURL(href: string, url: string): Promise<FilterResult> {
-    return await filterUrl({
-      href: href,
-      url: url,
+  private urlRuleOptions(): UrlRuleOptions {
+    return {
       baseUrl: this.baseUrl,
       excludes: this.excludes,
       ignoreRobotsTxt: this.ignoreRobotsTxt,
       robotsTxt: this.robotsTxt,
       allowExternalContentLinks: this.allowExternalContentLinks,
       allowSubdomains: this.allowSubdomains,
+    };
+  }
+
+  public async filterURL(href: string, url: string): Promise<FilterResult> {
+    return await filterUrl({
+      href: href,
+      url: url,
+      rules: this.urlRuleOptions(),
     });
   }
 
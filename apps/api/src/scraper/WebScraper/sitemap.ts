@@ -152,7 +152,7 @@ export async function getLinksFromSitemap(
 
     let instructions;
     try {
-      instructions = await processSitemap(content);
+      instructions = await processSitemap(content, { baseUrl: sitemapUrl });
     } catch (error) {
       logger.warn(
         "Rust sitemap processing failed, falling back to JavaScript logic",
@@ -165,7 +165,7 @@ export async function getLinksFromSitemap(
 
       let parsed;
       try {
-        parsed = await parseSitemapXml(content);
+        parsed = await parseSitemapXml(content, sitemapUrl);
       } catch (parseError) {
         logger.warn(
           "Rust XML parsing failed, falling back to JavaScript logic",
@@ -4,10 +4,8 @@ import { logger as _logger } from "../../lib/logger";
 import { Engine } from "../scrapeURL/engines";
 import { scrapeURL } from "../scrapeURL";
 import { CostTracking } from "../../lib/cost-tracking";
-import { processSitemap } from "@mendable/firecrawl-rs";
+import { processSitemap, processSitemapBytes } from "@mendable/firecrawl-rs";
 import { fetchFileToBuffer } from "../scrapeURL/engines/utils/downloadFile";
-import { gunzip } from "node:zlib";
-import { promisify } from "node:util";
 
 const useFireEngine =
   process.env.FIRE_ENGINE_BETA_URL !== "" &&
@@ -27,17 +25,17 @@ type SitemapData = {
   sitemaps: URL[];
 };
 
-const gunzipAsync = promisify(gunzip);
-
+// Returned compressed; the native sitemap parser inflates it with a size guard.
 async function _getSitemapXMLGZ(
   options: SitemapScrapeOptions,
-): Promise<string> {
+): Promise<Buffer> {
   const { buffer } = await fetchFileToBuffer(options.url);
-  const decompressed = await gunzipAsync(buffer);
-  return decompressed.toString("utf-8");
+  return buffer;
 }
 
-async function getSitemapXML(options: SitemapScrapeOptions): Promise<string> {
+async function getSitemapXML(
+  options: SitemapScrapeOptions,
+): Promise<string | Buffer> {
   if (options.url.toLowerCase().endsWith(".gz")) {
     return await _getSitemapXMLGZ(options);
   }
@@ -116,7 +114,10 @@ export async function scrapeSitemap(
 
   logger.info("Processing sitemap");
 
-  const instructions = await processSitemap(xml);
+  const instructions =
+    typeof xml === "string"
+      ? await processSitemap(xml, { baseUrl: options.url })
+      : await processSitemapBytes(xml, { baseUrl: options.url });
 
   const sitemapData: SitemapData = {
     urls: [],
@@ -317,6 +317,7 @@ async function scrapeURLLoopIter(
       meta.url,
       scrapeOptions.parse({ onlyMainContent: true }),
     ),
+    meta.url,
   );
 
   if (checkMarkdown.trim().length === 0) {
@@ -326,6 +327,7 @@ async function scrapeURLLoopIter(
         meta.url,
         scrapeOptions.parse({ onlyMainContent: false }),
       ),
+      meta.url,
     );
   }
 
@@ -0,0 +1,53 @@
+import { extractMetadata } from "../extractMetadata";
+import { Meta } from "../..";
+import { logger } from "../../../../lib/logger";
+
+describe("extractMetadata integration", () => {
+  const meta = {
+    id: "test",
+    url: "https://example.org/foo/bar",
+    logger,
+  } as unknown as Meta;
+
+  it("should resolve link metadata against the base href", async () => {
+    const html = `
+      <html>
+        <head>
+          <base href="/docs/" />
+          <link rel="canonical" href="page" />
+          <link rel="next" href="page?p=2" />
+          <link rel="alternate" hreflang="de" href="de/page" />
+          <link rel="alternate" type="application/rss+xml" href="feed.xml" />
+        </head>
+        <body></body>
+      </html>
+    `;
+    const metadata = await extractMetadata(meta, html);
+    expect(metadata.canonicalUrl).toBe("https://example.org/docs/page");
+    expect(metadata.nextUrl).toBe("https://example.org/docs/page?p=2");
+    expect(metadata.hreflangAlternates).toEqual([
+      { hreflang: "de", href: "https://example.org/docs/de/page" },
+    ]);
+    expect(metadata.feeds?.[0].href).toBe("https://example.org/docs/feed.xml");
+  });
+
+  it("should resolve the favicon against the base href", async () => {
+    const html = `
+      <html>
+        <head>
+          <base href="https://cdn.example.org/assets/" />
+          <link rel="icon" href="favicon.ico" />
+        </head>
+        <body></body>
+      </html>
+    `;
+    const metadata = await extractMetadata(meta, html);
+    expect(metadata.favicon).toBe("https://cdn.example.org/assets/favicon.ico");
+  });
+
+  it("should resolve against the page URL without a base href", async () => {
+    const html = `<html><head><link rel="canonical" href="page" /></head></html>`;
+    const metadata = await extractMetadata(meta, html);
+    expect(metadata.canonicalUrl).toBe("https://example.org/foo/page");
+  });
+});
@@ -1,20 +1,61 @@
 import { load } from "cheerio"; // rustified
 import { Document } from "../../../controllers/v1/types";
 import { Meta } from "..";
-import { extractMetadata as _extractMetadata } from "@mendable/firecrawl-rs";
+import {
+  extractMetadata as _extractMetadata,
+  extractBaseHref as _extractBaseHref,
+} from "@mendable/firecrawl-rs";
 
 async function extractMetadataRust(
   meta: Meta,
   html: string,
 ): Promise<Partial<Document["metadata"]>> {
   const fromRust = await _extractMetadata(html);
+  const baseUrl = meta.rewrittenUrl ?? meta.url;
+  // Links in the document are relative to its <base href>, if any
+  const baseHref = await _extractBaseHref(html, baseUrl);
+  const resolve = (href: string) => {
+    try {
+      return new URL(href, baseHref).href;
+    } catch {
+      return href;
+    }
+  };
 
   return {
     ...fromRust,
-    ...(fromRust.favicon
+    ...(fromRust.favicon ? { favicon: resolve(fromRust.favicon) } : {}),
+    ...(fromRust.canonicalUrl
+      ? { canonicalUrl: resolve(fromRust.canonicalUrl) }
+      : {}),
+    ...(fromRust.nextUrl ? { nextUrl: resolve(fromRust.nextUrl) } : {}),
+    ...(fromRust.prevUrl ? { prevUrl: resolve(fromRust.prevUrl) } : {}),
+    ...(fromRust.hreflangAlternates
+      ? {
+          hreflangAlternates: fromRust.hreflangAlternates.map(
+            (x: { hreflang: string; href: string }) => ({
+              ...x,
+              href: resolve(x.href),
+            }),
+          ),
+        }
+      : {}),
+    ...(fromRust.feeds
+      ? {
+          feeds: fromRust.feeds.map(
+            (x: { href: string; type: string; title?: string }) => ({
+              ...x,
+              href: resolve(x.href),
+            }),
+          ),
+        }
+      : {}),
+    ...(fromRust.refresh?.url
       ? {
-          favicon: new URL(fromRust.favicon, meta.rewrittenUrl ?? meta.url)
-            .href,
+          refresh: {
+            ...fromRust.refresh,
+            url: resolve(fromRust.refresh.url),
+          },
         }
       : {}),
     scrapeId: meta.id,
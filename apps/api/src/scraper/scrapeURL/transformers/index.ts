@@ -83,7 +83,12 @@ async function deriveMarkdownFromHTML(
     return document;
   }
 
-  document.markdown = await parseMarkdown(document.html);
+  const url =
+    document.metadata.url ??
+    document.metadata.sourceURL ??
+    meta.rewrittenUrl ??
+    meta.url;
+  document.markdown = await parseMarkdown(document.html, url);
 
   if (
     meta.options.onlyMainContent === true &&
@@ -102,7 +107,7 @@ async function deriveMarkdownFromHTML(
     };
 
     document = await deriveHTMLFromRawHTML(fallbackMeta, document);
-    document.markdown = await parseMarkdown(document.html);
+    document.markdown = await parseMarkdown(document.html, url);
 
     meta.logger.info("Fallback to full content extraction completed", {
       markdownLength: document.markdown?.length || 0,
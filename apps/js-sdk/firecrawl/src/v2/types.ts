@@ -164,12 +164,42 @@ export interface DocumentMetadata {
   ogDescription?: string;
   ogUrl?: string;
   ogImage?: string;
+  canonicalUrl?: string;
+  hreflangAlternates?: HreflangAlternate[];
+  feeds?: FeedLink[];
+  twitterCard?: string;
+  twitterSite?: string;
+  twitterCreator?: string;
+  twitterTitle?: string;
+  twitterDescription?: string;
+  twitterImage?: string;
+  nextUrl?: string;
+  prevUrl?: string;
+  refresh?: MetaRefresh;
   sourceURL?: string;
   statusCode?: number;
   error?: string;
   [key: string]: unknown;
 }
 
+export interface HreflangAlternate {
+  hreflang: string;
+  href: string;
+}
+
+export interface FeedLink {
+  href: string;
+  /** `application/rss+xml`, `application/atom+xml` or `application/feed+json`. */
+  type: string;
+  title?: string;
+}
+
+export interface MetaRefresh {
+  /** Seconds before the page refreshes or redirects. */
+  delay: number;
+  url?: string;
+}
+
 export interface Document {
   markdown?: string;
   html?: string;
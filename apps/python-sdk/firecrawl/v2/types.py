@@ -32,6 +32,22 @@ class BaseResponse(BaseModel, Generic[T]):
     warning: Optional[str] = None
 
 # Document and content types
+class HreflangAlternate(BaseModel):
+    """A language variant of the page from `link[rel=alternate][hreflang]`."""
+    hreflang: str
+    href: str
+
+class FeedLink(BaseModel):
+    """An RSS, Atom or JSON feed advertised by the page."""
+    href: str
+    type: str
+    title: Optional[str] = None
+
+class MetaRefresh(BaseModel):
+    """A `meta[http-equiv=refresh]` directive."""
+    delay: int
+    url: Optional[str] = None
+
 class DocumentMetadata(BaseModel):
     """Metadata for scraped documents (snake_case only; API camelCase normalized in code)."""
     # Common metadata fields
@@ -72,6 +88,20 @@ class DocumentMetadata(BaseModel):
     article_tag: Optional[str] = None
     article_section: Optional[str] = None
 
+    # Link relations and Twitter cards
+    canonical_url: Optional[str] = None
+    hreflang_alternates: Optional[List[HreflangAlternate]] = None
+    feeds: Optional[List[FeedLink]] = None
+    twitter_card: Optional[str] = None
+    twitter_site: Optional[str] = None
+    twitter_creator: Optional[str] = None
+    twitter_title: Optional[str] = None
+    twitter_description: Optional[str] = None
+    twitter_image: Optional[str] = None
+    next_url: Optional[str] = None
+    prev_url: Optional[str] = None
+    refresh: Optional[MetaRefresh] = None
+
     # Response-level metadata
     source_url: Optional[str] = None
     status_code: Optional[int] = None
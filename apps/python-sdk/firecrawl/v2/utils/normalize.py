@@ -38,6 +38,17 @@ def _map_metadata_keys(md: Dict[str, Any]) -> Dict[str, Any]:
         "publishedTime": "published_time",
         "articleTag": "article_tag",
         "articleSection": "article_section",
+        # Link relations and Twitter cards
+        "canonicalUrl": "canonical_url",
+        "hreflangAlternates": "hreflang_alternates",
+        "twitterCard": "twitter_card",
+        "twitterSite": "twitter_site",
+        "twitterCreator": "twitter_creator",
+        "twitterTitle": "twitter_title",
+        "twitterDescription": "twitter_description",
+        "twitterImage": "twitter_image",
+        "nextUrl": "next_url",
+        "prevUrl": "prev_url",
         # Response-level
         "sourceURL": "source_url",
         "statusCode": "status_code",
@@ -47,6 +47,48 @@ pub struct DocumentMetadata {
     pub dc_date: Option<String>,
     pub dc_date_created: Option<String>,
     pub dcterms_created: Option<String>,
+
+    // link relations
+    pub canonical_url: Option<String>,
+    pub hreflang_alternates: Option<Vec<HreflangAlternate>>,
+    pub feeds: Option<Vec<FeedLink>>,
+    pub next_url: Option<String>,
+    pub prev_url: Option<String>,
+    pub refresh: Option<MetaRefresh>,
+
+    // twitter: namespace
+    pub twitter_card: Option<String>,
+    pub twitter_site: Option<String>,
+    pub twitter_creator: Option<String>,
+    pub twitter_title: Option<String>,
+    pub twitter_description: Option<String>,
+    pub twitter_image: Option<String>,
+}
+
+/// A language variant of the page, from `<link rel="alternate" hreflang>`.
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct HreflangAlternate {
+    pub hreflang: String,
+    pub href: String,
+}
+
+/// An RSS, Atom or JSON feed advertised by the page.
+#[serde_with::skip_serializing_none]
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct FeedLink {
+    pub href: String,
+    #[serde(rename = "type")]
+    pub feed_type: String,
+    pub title: Option<String>,
+}
+
+/// A `<meta http-equiv="refresh">` directive.
+#[serde_with::skip_serializing_none]
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct MetaRefresh {
+    /// Seconds before the page refreshes or redirects.
+    pub delay: u64,
+    pub url: Option<String>,
 }
 
 #[serde_with::skip_serializing_none]